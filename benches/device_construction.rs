@@ -0,0 +1,68 @@
+//! Measures the cost of creating a `Udev` context for every device.
+//!
+//! Every device in the system is constructed from its syspath three times: with a new context for
+//! each device, with the convenience constructor `Device::from_syspath()`, and through a
+//! `DeviceFactory` sharing one context. Without the `send` feature, the convenience constructor
+//! reuses a per-thread context, so it should be about as fast as the factory.
+//!
+//! Run with `cargo bench --bench device_construction`, optionally with `--features send`.
+
+extern crate udev;
+
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 10;
+
+fn bench<F: FnMut(&PathBuf)>(name: &str, syspaths: &[PathBuf], mut f: F) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        for syspath in syspaths {
+            f(syspath);
+        }
+    }
+
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!(
+        "{:<20} {:>12?} for {} devices",
+        name,
+        elapsed,
+        syspaths.len()
+    );
+    elapsed
+}
+
+fn main() {
+    let syspaths: Vec<PathBuf> = udev::Enumerator::new()
+        .unwrap()
+        .scan_devices()
+        .unwrap()
+        .map(|device| device.syspath().to_owned())
+        .collect();
+
+    if syspaths.is_empty() {
+        println!("no devices found");
+        return;
+    }
+
+    let fresh = bench("new context", &syspaths, |syspath| {
+        let udev = udev::Udev::new().unwrap();
+        let _ = udev::Device::from_syspath_with_context(udev, syspath);
+    });
+
+    bench("from_syspath", &syspaths, |syspath| {
+        let _ = udev::Device::from_syspath(syspath);
+    });
+
+    let factory = udev::DeviceFactory::new().unwrap();
+
+    let shared = bench("DeviceFactory", &syspaths, |syspath| {
+        let _ = factory.device(syspath);
+    });
+
+    println!(
+        "context creation: {:?} per device",
+        fresh.checked_sub(shared).unwrap_or_default() / syspaths.len() as u32
+    );
+}
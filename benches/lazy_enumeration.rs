@@ -0,0 +1,66 @@
+//! Measures the memory used while iterating devices with `Enumerator::devices_lazy()`.
+//!
+//! The scan is repeated until tens of thousands of devices have been iterated, sampling the heap
+//! usage after every device. The peak should stay the same no matter how many devices that takes.
+//! For comparison, the same devices are also collected into a `Vec`.
+//!
+//! Heap usage is read with glibc's `mallinfo2()`, which covers the allocations made by libudev as
+//! well as those made on the Rust side.
+//!
+//! Run with `cargo bench --bench lazy_enumeration`.
+
+extern crate libc;
+extern crate udev;
+
+use std::time::Instant;
+
+const TARGET_DEVICES: usize = 50_000;
+
+/// Returns the number of bytes currently allocated on the heap.
+fn heap_in_use() -> usize {
+    unsafe { libc::mallinfo2() }.uordblks
+}
+
+fn main() {
+    let mut enumerator = udev::Enumerator::new().unwrap();
+
+    let start = Instant::now();
+    let mut iterated = 0;
+    let mut peak = 0;
+
+    while iterated < TARGET_DEVICES {
+        let devices = enumerator.devices_lazy().unwrap();
+        let baseline = heap_in_use();
+        let before = iterated;
+
+        for device in devices {
+            assert!(device.syspath().is_absolute());
+            iterated += 1;
+
+            peak = peak.max(heap_in_use().saturating_sub(baseline));
+        }
+
+        if iterated == before {
+            println!("no devices found");
+            return;
+        }
+    }
+
+    println!(
+        "devices_lazy: {} devices in {:?}, peak {} bytes above the scan while iterating",
+        iterated,
+        start.elapsed(),
+        peak
+    );
+
+    let baseline = heap_in_use();
+    let start = Instant::now();
+    let devices: Vec<udev::Device> = enumerator.scan_devices().unwrap().collect();
+
+    println!(
+        "scan_devices + collect: {} devices in {:?}, {} bytes including the scan",
+        devices.len(),
+        start.elapsed(),
+        heap_in_use().saturating_sub(baseline)
+    );
+}
@@ -0,0 +1,55 @@
+//! Measures where the time of an enumeration goes.
+//!
+//! libudev always sorts the scanned devices, so there is no unsorted scan to compare against.
+//! Instead, this compares scanning alone with scanning followed by constructing every device.
+//!
+//! Run with `cargo bench --bench scan_devices`, optionally passing a subsystem to enumerate, e.g.
+//! `cargo bench --bench scan_devices -- block`.
+
+extern crate udev;
+
+use std::env;
+use std::time::{Duration, Instant};
+
+const ITERATIONS: u32 = 50;
+
+fn bench<F: FnMut(&mut udev::Enumerator)>(
+    name: &str,
+    enumerator: &mut udev::Enumerator,
+    mut f: F,
+) -> Duration {
+    let start = Instant::now();
+
+    for _ in 0..ITERATIONS {
+        f(enumerator);
+    }
+
+    let elapsed = start.elapsed() / ITERATIONS;
+    println!("{:<16} {:>12?} per scan", name, elapsed);
+    elapsed
+}
+
+fn main() {
+    let mut enumerator = udev::Enumerator::new().unwrap();
+
+    // `cargo bench` passes `--bench` to the benchmark; skip flags.
+    if let Some(subsystem) = env::args().skip(1).find(|arg| !arg.starts_with('-')) {
+        enumerator.match_subsystem(subsystem).unwrap();
+    }
+
+    let devices = enumerator.scan_devices().unwrap().count();
+    println!("{} devices", devices);
+
+    let scan = bench("scan only", &mut enumerator, |en| {
+        en.scan_devices().unwrap();
+    });
+
+    let sorted = bench("scan_devices", &mut enumerator, |en| {
+        en.scan_devices().unwrap().for_each(drop);
+    });
+
+    println!(
+        "device construction: {:?}",
+        sorted.checked_sub(scan).unwrap_or_default()
+    );
+}
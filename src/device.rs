@@ -1,19 +1,38 @@
 use std::str;
 
-use std::ffi::{CStr, CString, OsStr};
+use std::collections::{HashMap, HashSet};
+use std::ffi::{CStr, CString, OsStr, OsString};
+use std::fs;
 use std::io::Result;
+use std::iter::{self, FusedIterator};
 use std::marker::PhantomData;
-use std::path::Path;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::MetadataExt;
+use std::path::{Component, Path, PathBuf};
 use std::ptr;
 use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
 
 use libc::{c_char, dev_t};
 
+#[cfg(feature = "hwdb")]
+use hwdb::Hwdb;
 use list::{Entry, EntryList};
+use match_expr::MatchExpr;
+use subsystem::{BlockDevice, FsInfo, InputCaps, NetDevice, Partition, PowerSupply, Subsystem};
 use Udev;
 use {ffi, util};
 
-use {AsRaw, FromRaw};
+use {AsRaw, Enumerator, EventType, FromRaw};
+
+// Added in systemd 247, so it's missing from `libudev-sys`.
+#[cfg(feature = "current_tags")]
+extern "C" {
+    fn udev_device_get_current_tags_list_entry(
+        udev_device: *mut ffi::udev_device,
+    ) -> *mut ffi::udev_list_entry;
+}
 
 /// A structure that provides access to sysfs/kernel devices.
 pub struct Device {
@@ -25,6 +44,7 @@ pub struct Device {
 ///
 /// See also [`from_devnum`][crate::Device::from_devnum].
 #[repr(u8)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DeviceType {
     /// UNIX character-style file IO semantics.
     Character = b'c',
@@ -68,12 +88,67 @@ impl Drop for Device {
     }
 }
 
+/// Devices are equal if they have the same syspath.
+impl PartialEq for Device {
+    fn eq(&self, other: &Self) -> bool {
+        self.syspath() == other.syspath()
+    }
+}
+
+impl Eq for Device {}
+
+/// Devices are hashed by syspath, consistent with `==`, so they can be used as `HashMap` keys.
+impl std::hash::Hash for Device {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.syspath().hash(state);
+    }
+}
+
+/// Devices are ordered lexicographically by syspath.
+///
+/// This gives a stable order that doesn't depend on the device topology, unlike the dependency
+/// order of `Enumerator::scan_devices()`.
+impl PartialOrd for Device {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Device {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.syspath().cmp(other.syspath())
+    }
+}
+
 #[cfg(feature = "send")]
 unsafe impl Send for Device {}
 #[cfg(feature = "sync")]
 unsafe impl Sync for Device {}
 
-as_ffi_with_context!(Device, device, ffi::udev_device, ffi::udev_device_ref);
+as_ffi_with_context!(
+    Device,
+    device,
+    ffi::udev_device,
+    ffi::udev_device_ref,
+    ffi::udev_device_get_udev
+);
+
+/// Subsystems of devices that sit on a bus, as used by `Device::bus_parent()`.
+const BUS_SUBSYSTEMS: &[&str] = &[
+    "usb",
+    "pci",
+    "platform",
+    "scsi",
+    "i2c",
+    "spi",
+    "virtio",
+    "serio",
+    "hid",
+    "mmc",
+    "sdio",
+    "pnp",
+    "thunderbolt",
+];
 
 /// A convenience alias for a list of properties, bound to a device.
 pub type Properties<'a> = EntryList<'a, Device>;
@@ -84,20 +159,34 @@ pub struct Attributes<'a> {
     device: &'a Device,
 }
 
+/// An iterator over the symlinks that udev created for a device node, as returned by
+/// `Device::devlinks()`.
+pub struct DevLinks<'a> {
+    entries: EntryList<'a, Device>,
+}
+
+/// An iterator over the tags of a device, as returned by `Device::tags()` and
+/// `Device::current_tags()`.
+pub struct Tags<'a> {
+    entries: EntryList<'a, Device>,
+}
+
+/// An iterator over the parents of a device, nearest first, as returned by `Device::ancestors()`.
+pub struct Ancestors {
+    next: Option<Device>,
+}
+
 impl Device {
     /// Creates a device for a given syspath.
     ///
     /// The `syspath` parameter should be a path to the device file within the `sysfs` file system,
     /// e.g., `/sys/devices/virtual/tty/tty0`.
-    pub fn from_syspath(syspath: &Path) -> Result<Self> {
-        // Create a new Udev context for this device
-        // It would be more efficient to allow callers to create just one context and use multiple
-        // devices, however that would be an API-breaking change.
-        //
-        // When devices are enumerated using an `Enumerator`, it will use
-        // `from_syspath_with_context` which can reuse the existing `Udev` context to avoid this
-        // extra overhead.
-        let udev = Udev::new()?;
+    ///
+    /// Symlinks such as `/sys/class/net/eth0` are accepted as well, but they are resolved, so the
+    /// device's `syspath()` is then the path below `/sys/devices` rather than the given path.
+    pub fn from_syspath<P: AsRef<Path>>(syspath: P) -> Result<Self> {
+        // Reuse this thread's Udev context, so back-to-back calls don't each create one.
+        let udev = Udev::cached()?;
 
         Self::from_syspath_with_context(udev, syspath)
     }
@@ -107,8 +196,8 @@ impl Device {
     ///
     /// The `syspath` parameter should be a path to the device file within the `sysfs` file system,
     /// e.g., `/sys/devices/virtual/tty/tty0`.
-    pub fn from_syspath_with_context(udev: Udev, syspath: &Path) -> Result<Self> {
-        let syspath = util::os_str_to_cstring(syspath)?;
+    pub fn from_syspath_with_context<P: AsRef<Path>>(udev: Udev, syspath: P) -> Result<Self> {
+        let syspath = util::os_str_to_cstring(syspath.as_ref())?;
 
         let ptr = try_alloc!(unsafe {
             ffi::udev_device_new_from_syspath(udev.as_raw(), syspath.as_ptr())
@@ -117,6 +206,33 @@ impl Device {
         Ok(Self::from_raw(udev, ptr))
     }
 
+    /// Creates a device for a given class and name, e.g. `net` and `eth0`.
+    ///
+    /// This looks up `/sys/class/<class>/<name>`. As with `from_syspath()`, the device's
+    /// `syspath()` is the resolved path below `/sys/devices`, not the class path. Names containing
+    /// a `/`, or that are empty, `.` or `..`, are rejected with `EINVAL`.
+    pub fn from_class_path(class: &OsStr, name: &OsStr) -> Result<Self> {
+        Self::from_class_path_with_context(Udev::cached()?, class, name)
+    }
+
+    /// Creates a device for a given class and name, e.g. `net` and `eth0`, using an existing
+    /// `Udev` instance rather than creating one automatically.
+    ///
+    /// See `from_class_path()`.
+    pub fn from_class_path_with_context(udev: Udev, class: &OsStr, name: &OsStr) -> Result<Self> {
+        fn is_component(name: &OsStr) -> bool {
+            !name.is_empty() && name != "." && name != ".." && !name.as_bytes().contains(&b'/')
+        }
+
+        if !is_component(class) || !is_component(name) {
+            return Err(std::io::Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        let syspath = Path::new("/sys/class").join(class).join(name);
+
+        Self::from_syspath_with_context(udev, syspath)
+    }
+
     /// Create new udev device, and fill in information from the sys device
     /// and the udev database entry.
     ///
@@ -130,7 +246,7 @@ impl Device {
             .ok()
             .ok_or(std::io::Error::from_raw_os_error(libc::EINVAL))?;
 
-        let udev = Udev::new()?;
+        let udev = Udev::cached()?;
 
         let ptr = try_alloc!(unsafe {
             ffi::udev_device_new_from_subsystem_sysname(
@@ -251,7 +367,7 @@ impl Device {
     /// [`libc::major`], [`libc::minor`], [`libc::makedev`] and equivalent functionality from
     /// higher-level rust crates.
     pub fn from_devnum(dev_type: self::DeviceType, devnum: dev_t) -> Result<Self> {
-        let udev = Udev::new()?;
+        let udev = Udev::cached()?;
 
         Self::from_devnum_with_context(udev, dev_type, devnum)
     }
@@ -272,6 +388,58 @@ impl Device {
         Ok(Self::from_raw(udev, ptr))
     }
 
+    /// Creates a rust udev `Device` for a device node, such as `/dev/sda`, or a symbolic link to
+    /// one, such as those under `/dev/disk/by-id/`.
+    ///
+    /// This looks up the type and number of the device special file and calls
+    /// [`from_devnum`][Self::from_devnum], as in its example. Fails with an error of kind
+    /// `InvalidInput` if the path is not a character or block special file.
+    pub fn from_devnode<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Self::from_devnode_with_context(Udev::cached()?, path)
+    }
+
+    /// Creates a rust udev `Device` for a device node, using an existing [`Udev`] instance rather
+    /// than creating one automatically.
+    ///
+    /// See [`from_devnode`][Self::from_devnode] for detailed usage.
+    pub fn from_devnode_with_context<P: AsRef<Path>>(udev: Udev, path: P) -> Result<Self> {
+        let metadata = fs::metadata(path)?;
+
+        let dev_type = match metadata.mode() & libc::S_IFMT {
+            libc::S_IFCHR => DeviceType::Character,
+            libc::S_IFBLK => DeviceType::Block,
+            _ => {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    "not a character or block special file",
+                ))
+            }
+        };
+
+        Self::from_devnum_with_context(udev, dev_type, metadata.rdev() as dev_t)
+    }
+
+    /// Creates a device from the process environment, as set up by udev for the programs it runs
+    /// from rules, e.g. with `RUN` or `IMPORT{program}`.
+    ///
+    /// The environment holds the device's properties, such as `DEVPATH`, `SUBSYSTEM`, `ACTION` and
+    /// `SEQNUM`, which are then available from the device as for an event received from a
+    /// monitor. Fails if the mandatory properties are missing, i.e. when the program was not run
+    /// by udev.
+    pub fn from_environment() -> Result<Self> {
+        Self::from_environment_with_context(Udev::cached()?)
+    }
+
+    /// Creates a device from the process environment, using an existing `Udev` instance rather
+    /// than creating one automatically.
+    ///
+    /// See `from_environment()`.
+    pub fn from_environment_with_context(udev: Udev) -> Result<Self> {
+        let ptr = try_alloc!(unsafe { ffi::udev_device_new_from_environment(udev.as_raw()) });
+
+        Ok(Self::from_raw(udev, ptr))
+    }
+
     /// Creates a rust `Device` given an already created libudev `ffi::udev_device*` and a
     /// corresponding `Udev` instance from which the device was created.
     ///
@@ -292,6 +460,32 @@ impl Device {
         unsafe { ffi::udev_device_get_is_initialized(self.device) > 0 }
     }
 
+    /// Returns the number of microseconds since udev finished initializing the device.
+    ///
+    /// Returns `None` if the device is not initialized, or has no entry in the udev database.
+    pub fn usec_since_initialized(&self) -> Option<u64> {
+        match unsafe { ffi::udev_device_get_usec_since_initialized(self.device) } {
+            0 => None,
+            usec => Some(usec),
+        }
+    }
+
+    /// Returns the time elapsed since udev finished initializing the device.
+    ///
+    /// This is useful to debounce devices that just appeared. Returns `None` if the device is not
+    /// initialized, see `usec_since_initialized()`.
+    pub fn since_initialized(&self) -> Option<Duration> {
+        self.usec_since_initialized().map(Duration::from_micros)
+    }
+
+    /// Returns the approximate time at which udev finished initializing the device.
+    ///
+    /// This is computed from the time elapsed since initialization, as that is all libudev
+    /// records. Returns `None` if the device is not initialized.
+    pub fn initialized_at(&self) -> Option<SystemTime> {
+        SystemTime::now().checked_sub(self.since_initialized()?)
+    }
+
     /// Gets the device's major/minor number.
     pub fn devnum(&self) -> Option<dev_t> {
         match unsafe { ffi::udev_device_get_devnum(self.device) } {
@@ -300,6 +494,33 @@ impl Device {
         }
     }
 
+    /// Returns the device's major and minor numbers, e.g. `(8, 0)` for `/dev/sda`.
+    ///
+    /// This is `devnum()` split into its parts, as shown by `ls -l` or listed in `/proc/devices`.
+    pub fn device_number(&self) -> Option<(u32, u32)> {
+        let devnum = self.devnum()?;
+
+        Some((libc::major(devnum), libc::minor(devnum)))
+    }
+
+    /// Returns the type and number of the device's special file, if it has one.
+    ///
+    /// Passing the result to [`from_devnum`][Self::from_devnum] looks the device up again, which
+    /// makes it a compact identity to persist. As with `from_devnum`, devices in the `block`
+    /// subsystem are block devices and all others are character devices. Device numbers may be
+    /// reassigned across reboots and hot-plug events.
+    pub fn identity(&self) -> Option<(DeviceType, dev_t)> {
+        let devnum = self.devnum()?;
+
+        let dev_type = if self.subsystem() == Some(OsStr::new("block")) {
+            DeviceType::Block
+        } else {
+            DeviceType::Character
+        };
+
+        Some((dev_type, devnum))
+    }
+
     /// Returns the syspath of the device.
     ///
     /// The path is an absolute path and includes the sys mount point. For example, the syspath for
@@ -311,6 +532,23 @@ impl Device {
         })
     }
 
+    /// Returns the syspath of the device with all symlinks resolved.
+    ///
+    /// The result is the symlink-free path in sysfs, even for devices that were looked up through
+    /// a symlink such as `/sys/class/net/eth0`, so it can be used to tell whether two devices
+    /// refer to the same sysfs entry. Fails if the device no longer exists in sysfs.
+    pub fn canonical_syspath(&self) -> Result<PathBuf> {
+        fs::canonicalize(self.syspath())
+    }
+
+    /// Checks whether the device still exists in sysfs.
+    ///
+    /// This only checks that the syspath exists, so it's much cheaper than looking the device up
+    /// again. A different device may have appeared at the same syspath in the meantime.
+    pub fn still_present(&self) -> bool {
+        self.syspath().exists()
+    }
+
     /// Returns the kernel devpath value of the device.
     ///
     /// The path does not contain the sys mount point, but does start with a `/`. For example, the
@@ -328,6 +566,61 @@ impl Device {
             .map(|path| Path::new(path))
     }
 
+    /// Returns the canonical path to the device node, e.g. `/dev/sda`.
+    ///
+    /// udev creates a single device node for a device, which is what `devnode()` returns, and any
+    /// number of symlinks pointing to it, e.g. `/dev/disk/by-id/...`, which are returned by
+    /// `devlinks()`. The device node is normally not a symlink itself, but if it is, it's resolved
+    /// here. If the device node doesn't exist, e.g. because the device was removed, `devnode()` is
+    /// returned as is.
+    pub fn canonical_devnode(&self) -> Option<PathBuf> {
+        let devnode = self.devnode()?;
+
+        match fs::symlink_metadata(devnode) {
+            Ok(metadata) if metadata.file_type().is_symlink() => fs::canonicalize(devnode)
+                .ok()
+                .or_else(|| Some(devnode.to_owned())),
+            _ => Some(devnode.to_owned()),
+        }
+    }
+
+    /// Returns an iterator over the symlinks that udev created for the device node, e.g.
+    /// `/dev/disk/by-id/...`.
+    ///
+    /// The symlinks all point to the device node returned by `devnode()`. The iterator is empty
+    /// for devices without a device node or without symlinks.
+    pub fn devlinks(&self) -> DevLinks<'_> {
+        DevLinks {
+            entries: EntryList {
+                entry: unsafe { ffi::udev_device_get_devlinks_list_entry(self.device) },
+                udev: None,
+                phantom: PhantomData,
+            },
+        }
+    }
+
+    /// Opens the device node with the given options.
+    ///
+    /// The access mode is taken from `options`, so e.g. `OpenOptions::new().read(true)` opens the
+    /// node read-only. Fails with `ENODEV` if the device has no device node, and otherwise with
+    /// whatever error opening the node gives, e.g. `EACCES` if the caller lacks permission.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// use std::fs::OpenOptions;
+    ///
+    /// let device = udev::Device::from_syspath("/sys/class/hidraw/hidraw0").unwrap();
+    /// let file = device.open(OpenOptions::new().read(true).write(true)).unwrap();
+    /// ```
+    pub fn open(&self, options: &fs::OpenOptions) -> Result<fs::File> {
+        let devnode = self
+            .devnode()
+            .ok_or_else(|| std::io::Error::from_raw_os_error(libc::ENODEV))?;
+
+        options.open(devnode)
+    }
+
     /// Returns the parent of the device.
     pub fn parent(&self) -> Option<Self> {
         let ptr = unsafe { ffi::udev_device_get_parent(self.device) };
@@ -341,6 +634,26 @@ impl Device {
         }))
     }
 
+    /// Returns an iterator over the parents of the device, starting with its direct parent and
+    /// ending with the root of its device tree.
+    ///
+    /// The device itself is not included. The parents share the device's `Udev` context.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let device = udev::Device::from_syspath("/sys/class/input/event0").unwrap();
+    ///
+    /// let controller = device
+    ///     .ancestors()
+    ///     .find(|parent| parent.devtype() == Some("usb_device".as_ref()));
+    /// ```
+    pub fn ancestors(&self) -> Ancestors {
+        Ancestors {
+            next: self.parent(),
+        }
+    }
+
     /// Returns the parent of the device with the matching subsystem and devtype if any.
     pub fn parent_with_subsystem<T: AsRef<OsStr>>(&self, subsystem: T) -> Result<Option<Self>> {
         let subsystem = util::os_str_to_cstring(subsystem)?;
@@ -386,6 +699,122 @@ impl Device {
         })))
     }
 
+    /// Returns the nearest parent of the device with the matching devtype, whatever its subsystem.
+    ///
+    /// libudev requires a subsystem when looking up a parent by devtype, so this walks the
+    /// device's `ancestors()` instead. As with the other `parent_with_*` methods, the device
+    /// itself is not considered, and a devtype containing a nul byte is rejected with `EINVAL`.
+    pub fn parent_with_devtype<U: AsRef<OsStr>>(&self, devtype: U) -> Result<Option<Self>> {
+        let devtype = devtype.as_ref();
+
+        if devtype.as_bytes().contains(&0) {
+            return Err(std::io::Error::from_raw_os_error(libc::EINVAL));
+        }
+
+        Ok(self
+            .ancestors()
+            .find(|parent| parent.devtype() == Some(devtype)))
+    }
+
+    /// Returns the nearest ancestor of the device that sits on a bus, such as a USB or PCI device.
+    ///
+    /// Intermediate sysfs nodes between the device and its bus device are skipped. The bus
+    /// subsystems considered are `usb`, `pci`, `platform`, `scsi`, `i2c`, `spi`, `virtio`,
+    /// `serio`, `hid`, `mmc`, `sdio`, `pnp` and `thunderbolt`. Returns `None` for virtual devices
+    /// that are not attached to any bus.
+    pub fn bus_parent(&self) -> Option<Self> {
+        self.ancestors().find(|device| {
+            device
+                .subsystem()
+                .is_some_and(|s| BUS_SUBSYSTEMS.iter().any(|bus| s == *bus))
+        })
+    }
+
+    /// Returns the nearest device, starting with the device itself and walking up its parents,
+    /// on which all of the given attributes have the expected values.
+    ///
+    /// All attributes must match on the same device, like the `ATTRS{}==` keys of a single udev
+    /// rule. Attributes that live on different devices, such as `bInterfaceNumber` on a USB
+    /// interface and `idVendor` on its parent USB device, must be matched by separate calls.
+    pub fn matching_ancestor_attributes(&self, attrs: &[(&OsStr, &OsStr)]) -> Option<Self> {
+        iter::once(self.clone())
+            .chain(self.ancestors())
+            .find(|device| {
+                attrs
+                    .iter()
+                    .all(|&(name, value)| device.attribute_value(name) == Some(value))
+            })
+    }
+
+    /// Returns the firmware node of the device, e.g. its ACPI device.
+    ///
+    /// This follows the device's `firmware_node` link in sysfs, so attributes such as `hid` or
+    /// `uid` that only exist on the firmware node can be read from the returned device. Returns
+    /// `None` if the device has no firmware node.
+    pub fn firmware_node(&self) -> Option<Self> {
+        let syspath = fs::canonicalize(self.syspath().join("firmware_node")).ok()?;
+
+        Self::from_syspath_with_context(self.udev.clone(), &syspath).ok()
+    }
+
+    /// Returns the direct children of the device, in dependency order.
+    ///
+    /// Grandchildren and other descendants further down the tree are not included.
+    pub fn children(&self) -> Result<Vec<Self>> {
+        let mut enumerator = Enumerator::with_udev(self.udev.clone())?;
+        enumerator.match_parent(self)?;
+
+        Ok(enumerator
+            .scan_devices()?
+            .filter(|device| {
+                device
+                    .parent()
+                    .is_some_and(|parent| parent.syspath() == self.syspath())
+            })
+            .collect())
+    }
+
+    /// Returns an iterator over the subtree rooted at the device, including the device itself.
+    ///
+    /// The tree is walked depth-first: each device is yielded before its children, which are
+    /// visited in the order returned by `children()`. Devices whose children can't be enumerated
+    /// are yielded without them, and devices that were already visited are skipped.
+    pub fn walk(&self) -> impl Iterator<Item = Self> {
+        let mut stack = vec![self.clone()];
+        let mut visited = HashSet::new();
+
+        std::iter::from_fn(move || {
+            while let Some(device) = stack.pop() {
+                if !visited.insert(device.syspath().to_owned()) {
+                    continue;
+                }
+
+                if let Ok(children) = device.children() {
+                    stack.extend(children.into_iter().rev());
+                }
+
+                return Some(device);
+            }
+
+            None
+        })
+    }
+
+    /// Returns the other children of the device's parent, e.g. the other partitions on the same
+    /// disk.
+    ///
+    /// The device itself is not included. Returns an empty list if the device has no parent.
+    pub fn siblings(&self) -> Result<Vec<Self>> {
+        let parent = match self.parent() {
+            Some(parent) => parent,
+            None => return Ok(Vec::new()),
+        };
+
+        let mut siblings = parent.children()?;
+        siblings.retain(|device| device.syspath() != self.syspath());
+        Ok(siblings)
+    }
+
     /// Returns the subsystem name of the device.
     ///
     /// The subsystem name is a string that indicates which kernel subsystem the device belongs to.
@@ -394,6 +823,13 @@ impl Device {
         unsafe { util::ptr_to_os_str(ffi::udev_device_get_subsystem(self.device)) }
     }
 
+    /// Returns the subsystem of the device as a `Subsystem`, to `match` on.
+    ///
+    /// Devices without a subsystem are reported as `Subsystem::Other` with an empty name.
+    pub fn subsystem_typed(&self) -> Subsystem {
+        Subsystem::from_name(self.subsystem().unwrap_or_else(|| OsStr::new("")))
+    }
+
     /// Returns the kernel device name for the device.
     ///
     /// The sysname is a string that differentiates the device from others in the same subsystem.
@@ -429,11 +865,140 @@ impl Device {
         unsafe { util::ptr_to_os_str(ffi::udev_device_get_devtype(self.device)) }
     }
 
+    /// Returns the devtype name of the device, inferring it if the devtype is missing.
+    ///
+    /// Devices from some events, such as `remove`, can lack a devtype even though it follows
+    /// from their subsystem. When `devtype()` returns `None`, the following heuristics are used:
+    ///
+    /// * `block` devices whose devpath places them directly below the `block` directory (e.g.
+    ///   `/devices/virtual/block/loop0`) are `disk`s, those nested below another block device
+    ///   (e.g. `.../block/sda/sda1`) are `partition`s.
+    /// * `usb` devices whose sysname contains a `:` (e.g. `1-1:1.0`) are `usb_interface`s, all
+    ///   others are `usb_device`s.
+    ///
+    /// Devices of other subsystems return `None`.
+    pub fn effective_devtype(&self) -> Option<OsString> {
+        if let Some(devtype) = self.devtype() {
+            return Some(devtype.to_owned());
+        }
+
+        let devtype = match self.subsystem().and_then(OsStr::to_str) {
+            Some("block") => {
+                let parent = Path::new(self.devpath()).parent().and_then(Path::file_name);
+
+                if parent == Some(OsStr::new("block")) {
+                    "disk"
+                } else {
+                    "partition"
+                }
+            }
+            Some("usb") => {
+                if self.sysname().as_bytes().contains(&b':') {
+                    "usb_interface"
+                } else {
+                    "usb_device"
+                }
+            }
+            _ => return None,
+        };
+
+        Some(OsString::from(devtype))
+    }
+
+    /// Returns a typed view of the device if it belongs to the `block` subsystem.
+    pub fn as_block(&self) -> Option<BlockDevice> {
+        if self.subsystem() != Some(OsStr::new("block")) {
+            return None;
+        }
+
+        Some(BlockDevice {
+            device: self.clone(),
+        })
+    }
+
+    /// Returns a typed view of the device if it belongs to the `net` subsystem.
+    pub fn as_net(&self) -> Option<NetDevice> {
+        if self.subsystem() != Some(OsStr::new("net")) {
+            return None;
+        }
+
+        Some(NetDevice {
+            device: self.clone(),
+        })
+    }
+
+    /// Returns a typed view of the device if it's a partition of a block device.
+    pub fn as_partition(&self) -> Option<Partition> {
+        if self.subsystem() != Some(OsStr::new("block"))
+            || self.devtype() != Some(OsStr::new("partition"))
+        {
+            return None;
+        }
+
+        Some(Partition {
+            device: self.clone(),
+        })
+    }
+
+    /// Returns a typed view of the device if it belongs to the `power_supply` subsystem.
+    pub fn as_power_supply(&self) -> Option<PowerSupply> {
+        if self.subsystem() != Some(OsStr::new("power_supply")) {
+            return None;
+        }
+
+        Some(PowerSupply {
+            device: self.clone(),
+        })
+    }
+
+    /// Returns the filesystem properties of the device, or `None` if udev found no filesystem or
+    /// other content signature on it, i.e. it has no `ID_FS_TYPE` property.
+    pub fn filesystem(&self) -> Option<FsInfo> {
+        self.property_value("ID_FS_TYPE")?;
+
+        Some(FsInfo {
+            device: self.clone(),
+        })
+    }
+
+    /// Returns the input capabilities of the device, or `None` if udev doesn't consider it an
+    /// input device, i.e. it has no `ID_INPUT=1` property.
+    pub fn input_capabilities(&self) -> Option<InputCaps> {
+        InputCaps::from_device(self)
+    }
+
+    /// Returns the MAC address of a network interface.
+    ///
+    /// Returns `None` if the device is not in the `net` subsystem, or if its address is not a
+    /// 6-byte Ethernet address. See `NetDevice::mac_address()`.
+    pub fn mac_address(&self) -> Option<[u8; 6]> {
+        self.as_net()?.mac_address()
+    }
+
+    /// Checks whether the device matches a udev rule-style match expression.
+    ///
+    /// See `MatchExpr` for the supported conditions and their semantics.
+    pub fn matches(&self, expr: &MatchExpr) -> bool {
+        expr.matches(self)
+    }
+
     /// Returns the name of the kernel driver attached to the device.
     pub fn driver(&self) -> Option<&OsStr> {
         unsafe { util::ptr_to_os_str(ffi::udev_device_get_driver(self.device)) }
     }
 
+    /// Returns the modalias of the device, e.g. `usb:v1D6Bp0002d0515dc09dsc00dp03ic09isc00ip00in00`.
+    ///
+    /// The modalias describes the device to the kernel's module loader, which loads the modules
+    /// whose aliases match it. It's available whether or not a driver is bound, so it's the
+    /// starting point for finding out which driver should bind to a device, e.g. with
+    /// `modprobe --resolve-alias`. The `MODALIAS` property is used if present, otherwise the
+    /// `modalias` attribute.
+    pub fn modalias(&self) -> Option<&OsStr> {
+        self.property_value("MODALIAS")
+            .or_else(|| self.attribute_value("modalias"))
+    }
+
     /// Retrieves the value of a device property.
     pub fn property_value<T: AsRef<OsStr>>(&self, property: T) -> Option<&OsStr> {
         let prop = match util::os_str_to_cstring(property) {
@@ -449,6 +1014,60 @@ impl Device {
         }
     }
 
+    /// Returns the seat the device is assigned to, from its `ID_SEAT` property.
+    ///
+    /// Like logind, devices without an `ID_SEAT` property, or with an empty one, are considered
+    /// to belong to `seat0`.
+    pub fn seat(&self) -> &OsStr {
+        match self.property_value("ID_SEAT") {
+            Some(seat) if !seat.is_empty() => seat,
+            _ => OsStr::new("seat0"),
+        }
+    }
+
+    /// Retrieves the value of a device property, matching the name case-insensitively.
+    ///
+    /// Property names are conventionally uppercase, so this is meant for names coming from users
+    /// or configuration files. Only ASCII letters are folded. If several properties match, the
+    /// first one is returned, preferring an exact match.
+    ///
+    /// Unlike `property_value()`, which libudev answers with a lookup, this scans the whole
+    /// property list, so it takes time linear in the number of properties.
+    pub fn property_value_ci<T: AsRef<OsStr>>(&self, property: T) -> Option<&OsStr> {
+        let property = property.as_ref();
+
+        if let Some(value) = self.property_value(property) {
+            return Some(value);
+        }
+
+        self.properties()
+            .find(|entry| {
+                entry
+                    .name
+                    .as_bytes()
+                    .eq_ignore_ascii_case(property.as_bytes())
+            })
+            .map(|entry| entry.value.unwrap_or_else(|| OsStr::new("")))
+    }
+
+    /// Retrieves the value of a device property, parsed with `FromStr`.
+    ///
+    /// Returns `None` if the property is missing, is not valid UTF-8 or fails to parse. Note that
+    /// udev's boolean flags, like `ID_INPUT_KEYBOARD`, are `1` rather than `true`, so they parse as
+    /// integers, not as `bool`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let device = udev::Device::from_syspath("/sys/class/input/event0").unwrap();
+    ///
+    /// let minor: Option<u32> = device.property_parse("MINOR");
+    /// let keyboard = device.property_parse::<u8, _>("ID_INPUT_KEYBOARD") == Some(1);
+    /// ```
+    pub fn property_parse<V: FromStr, T: AsRef<OsStr>>(&self, property: T) -> Option<V> {
+        self.property_value(property)?.to_str()?.parse().ok()
+    }
+
     /// Retrieves the value of a device attribute.
     pub fn attribute_value<T: AsRef<OsStr>>(&self, attribute: T) -> Option<&OsStr> {
         let attr = match util::os_str_to_cstring(attribute) {
@@ -464,6 +1083,62 @@ impl Device {
         }
     }
 
+    /// Retrieves the value of a device attribute, without a trailing newline.
+    ///
+    /// Sysfs attribute files usually end in a newline. Recent versions of libudev strip it
+    /// already, but older ones return the file contents as they are, so this strips a single
+    /// trailing newline if there is one. Use `attribute_value()` to get the value as returned by
+    /// libudev.
+    pub fn attribute_value_trimmed<T: AsRef<OsStr>>(&self, attribute: T) -> Option<&OsStr> {
+        let value = self.attribute_value(attribute)?.as_bytes();
+
+        Some(OsStr::from_bytes(
+            value.strip_suffix(b"\n").unwrap_or(value),
+        ))
+    }
+
+    /// Retrieves the value of a device attribute, parsed with `FromStr`.
+    ///
+    /// Surrounding whitespace, such as the trailing newline of most sysfs attributes, is ignored.
+    /// Returns `None` if the attribute is missing, is not valid UTF-8 or fails to parse.
+    pub fn attribute_parse<V: FromStr, T: AsRef<OsStr>>(&self, attribute: T) -> Option<V> {
+        self.attribute_value(attribute)?
+            .to_str()?
+            .trim()
+            .parse()
+            .ok()
+    }
+
+    /// Reads the raw contents of a device attribute.
+    ///
+    /// libudev returns attribute values as C strings, so `attribute_value()` ends at the first
+    /// nul byte, and libudev doesn't report the length of what it read. Binary attributes, such
+    /// as the `descriptors` of USB devices, are therefore read from sysfs directly, bypassing
+    /// libudev's cache. For text attributes, the bytes are the same as those of
+    /// `attribute_value()`, apart from the trailing newline that recent versions of libudev strip.
+    ///
+    /// Returns `None` if the attribute doesn't exist or can't be read, or if `attribute` is not a
+    /// relative path below the device's syspath.
+    pub fn attribute_bytes<T: AsRef<OsStr>>(&self, attribute: T) -> Option<Vec<u8>> {
+        fs::read(self.attribute_path(attribute.as_ref())?).ok()
+    }
+
+    /// Returns the path of a device attribute in sysfs, or `None` if `attribute` is not a relative
+    /// path below the device's syspath, such as an absolute path or one containing `..`.
+    fn attribute_path(&self, attribute: &OsStr) -> Option<PathBuf> {
+        let attribute = Path::new(attribute);
+
+        if attribute.as_os_str().is_empty()
+            || !attribute
+                .components()
+                .all(|c| matches!(c, Component::Normal(_)))
+        {
+            return None;
+        }
+
+        Some(self.syspath().join(attribute))
+    }
+
     /// Sets the value of a device attribute.
     pub fn set_attribute_value<T: AsRef<OsStr>, U: AsRef<OsStr>>(
         &mut self,
@@ -482,6 +1157,57 @@ impl Device {
         })
     }
 
+    /// Checks whether the current process may write a device attribute with
+    /// `set_attribute_value()`.
+    ///
+    /// Returns `None` if the attribute doesn't exist, or if `attr` is not a relative path below the
+    /// device's syspath. Attributes that the kernel doesn't allow to be written at all are
+    /// reported as read-only even to privileged processes.
+    pub fn attribute_is_writable<A: AsRef<OsStr>>(&self, attr: A) -> Option<bool> {
+        let path = self.attribute_path(attr.as_ref())?;
+        let metadata = fs::metadata(&path).ok()?;
+
+        if metadata.mode() & 0o222 == 0 {
+            return Some(false);
+        }
+
+        let path = util::os_str_to_cstring(&path).ok()?;
+
+        Some(unsafe { libc::access(path.as_ptr(), libc::W_OK) } == 0)
+    }
+
+    /// Waits until the value of a device attribute equals `expected`, returning whether it did so
+    /// before `timeout` elapsed.
+    ///
+    /// libudev caches attribute values, so the device is looked up again by its syspath for every
+    /// check. Fails if the device disappears while waiting.
+    pub fn wait_for_attribute<A: AsRef<OsStr>, V: AsRef<OsStr>>(
+        &self,
+        attribute: A,
+        expected: V,
+        timeout: Duration,
+    ) -> Result<bool> {
+        const INTERVAL: Duration = Duration::from_millis(10);
+
+        let deadline = Instant::now() + timeout;
+
+        loop {
+            let device = Self::from_syspath_with_context(self.udev.clone(), self.syspath())?;
+
+            if device.attribute_value(attribute.as_ref()) == Some(expected.as_ref()) {
+                return Ok(true);
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                return Ok(false);
+            }
+
+            thread::sleep(INTERVAL.min(deadline - now));
+        }
+    }
+
     /// Returns an iterator over the device's properties.
     ///
     /// ## Example
@@ -498,10 +1224,20 @@ impl Device {
     pub fn properties(&self) -> Properties {
         Properties {
             entry: unsafe { ffi::udev_device_get_properties_list_entry(self.device) },
+            udev: None,
             phantom: PhantomData,
         }
     }
 
+    /// Returns an iterator over the device's properties, skipping those that udev uses internally.
+    ///
+    /// udev prefixes the names of internal properties with a `.`, e.g. `.ID_FS_TYPE_NEW`. Like
+    /// `udevadm info`, this leaves them out.
+    pub fn public_properties(&self) -> impl Iterator<Item = Entry<'_>> {
+        self.properties()
+            .filter(|property| !property.name.as_bytes().starts_with(b"."))
+    }
+
     /// Returns an iterator over the device's attributes.
     ///
     /// ## Example
@@ -519,16 +1255,360 @@ impl Device {
         Attributes {
             entries: EntryList {
                 entry: unsafe { ffi::udev_device_get_sysattr_list_entry(self.device) },
+                udev: None,
                 phantom: PhantomData,
             },
             device: self,
         }
     }
 
+    /// Returns an iterator over the device's tags, e.g. `seat` or `uaccess`.
+    ///
+    /// ## Example
+    ///
+    /// ```no_run
+    /// let device = udev::Device::from_syspath("/sys/class/drm/card0").unwrap();
+    ///
+    /// for tag in device.tags() {
+    ///     println!("{:?}", tag);
+    /// }
+    /// ```
+    pub fn tags(&self) -> Tags<'_> {
+        Tags {
+            entries: EntryList {
+                entry: unsafe { ffi::udev_device_get_tags_list_entry(self.device) },
+                udev: None,
+                phantom: PhantomData,
+            },
+        }
+    }
+
+    /// Checks whether the device has the given tag.
+    ///
+    /// Returns `false` for tags that contain a nul byte, which no device can have.
+    pub fn has_tag<T: AsRef<OsStr>>(&self, tag: T) -> bool {
+        match util::os_str_to_cstring(tag) {
+            Ok(tag) => unsafe { ffi::udev_device_has_tag(self.device, tag.as_ptr()) > 0 },
+            Err(_) => false,
+        }
+    }
+
+    /// Returns an iterator over the tags that are currently set on the device.
+    ///
+    /// Tags are sticky: once a rule tags a device, `tags()` includes the tag until the device is
+    /// removed, even if the rules no longer set it when the device is processed again, e.g. on a
+    /// `change` event. This only includes the tags set by the latest processing of the device.
+    ///
+    /// Requires libudev from systemd 247 or newer, and the `current_tags` feature.
+    #[cfg(feature = "current_tags")]
+    pub fn current_tags(&self) -> Tags<'_> {
+        Tags {
+            entries: EntryList {
+                entry: unsafe { udev_device_get_current_tags_list_entry(self.device) },
+                udev: None,
+                phantom: PhantomData,
+            },
+        }
+    }
+
+    /// Returns the device's tags as owned strings.
+    pub fn tags_vec(&self) -> Vec<OsString> {
+        self.tags().map(OsStr::to_owned).collect()
+    }
+
+    /// Returns the tags listed in the device's `TAGS` property.
+    ///
+    /// Devices received from a monitor carry their tags in the `TAGS` property, in the form
+    /// `:tag1:tag2:`, but depending on the libudev version `tags_vec()` may be empty for them.
+    /// This parses the property instead, so it works for both monitored and enumerated devices.
+    pub fn tags_from_property(&self) -> impl Iterator<Item = &OsStr> {
+        self.property_value("TAGS")
+            .map_or(&[][..], OsStr::as_bytes)
+            .split(|&b| b == b':')
+            .filter(|tag| !tag.is_empty())
+            .map(OsStr::from_bytes)
+    }
+
+    /// Returns the device's properties as owned name/value pairs.
+    ///
+    /// Unlike `properties()`, the result does not borrow from the device.
+    pub fn properties_vec(&self) -> Vec<(OsString, OsString)> {
+        self.properties()
+            .map(|property| (property.name().to_owned(), property.value().to_owned()))
+            .collect()
+    }
+
+    /// Returns a snapshot of the device's properties, keyed by name.
+    ///
+    /// Unlike `properties()`, the result does not borrow from the device, so it can be kept after
+    /// the device is dropped.
+    pub fn property_map(&self) -> HashMap<OsString, OsString> {
+        self.properties()
+            .map(|property| (property.name().to_owned(), property.value().to_owned()))
+            .collect()
+    }
+
+    /// Returns a snapshot of the device's attribute values, keyed by name.
+    ///
+    /// Every attribute is read, which can be slow for devices with many attributes. Attributes
+    /// that can't be read, e.g. because they are write-only, are left out.
+    pub fn attribute_map(&self) -> HashMap<OsString, OsString> {
+        self.attributes()
+            .filter_map(|attr| Some((attr.name.to_owned(), attr.value?.to_owned())))
+            .collect()
+    }
+
+    /// Checks whether two devices have the same content: the same subsystem, devtype, properties
+    /// and attribute values.
+    ///
+    /// Unlike `==`, which compares syspaths, this compares what the devices report, e.g. to check a
+    /// device against a recorded fixture. Every attribute is read, so this is much slower than
+    /// `==`.
+    pub fn content_eq(&self, other: &Device) -> bool {
+        fn attributes(device: &Device) -> HashMap<OsString, Option<OsString>> {
+            device
+                .attributes()
+                .map(|attr| (attr.name.to_owned(), attr.value.map(OsStr::to_owned)))
+                .collect()
+        }
+
+        self.subsystem() == other.subsystem()
+            && self.devtype() == other.devtype()
+            && self.property_map() == other.property_map()
+            && attributes(self) == attributes(other)
+    }
+
+    /// Returns the device's properties, filled in with those of a fresh lookup of its syspath.
+    ///
+    /// Devices received in events may only carry a subset of their properties. This reads the
+    /// device again from sysfs and the udev database and adds any properties that are missing;
+    /// the device's own values take precedence. For devices that have been removed, the syspath no
+    /// longer exists, so only the device's own properties are returned.
+    pub fn enriched_properties(&self) -> HashMap<OsString, OsString> {
+        let mut properties = HashMap::new();
+
+        if let Ok(fresh) = Self::from_syspath_with_context(self.udev.clone(), self.syspath()) {
+            properties.extend(fresh.properties_vec());
+        }
+
+        properties.extend(self.properties_vec());
+        properties
+    }
+
+    /// Returns the raw contents of the device's `uevent` attribute.
+    ///
+    /// This is the `KEY=value` data that the kernel exposes for the device, one pair per line,
+    /// before any processing by udev rules. It may contain keys that aren't in `properties()`, and
+    /// vice versa. Returns `None` if the device has no `uevent` attribute or it isn't valid UTF-8.
+    pub fn uevent(&self) -> Option<String> {
+        self.attribute_value("uevent")?.to_str().map(str::to_owned)
+    }
+
+    /// Returns the contents of the device's `uevent` attribute as key/value pairs, in the order
+    /// the kernel lists them.
+    ///
+    /// Lines without a `=` are skipped. Returns an empty list if `uevent()` returns `None`.
+    pub fn uevent_pairs(&self) -> Vec<(String, String)> {
+        let uevent = match self.uevent() {
+            Some(uevent) => uevent,
+            None => return Vec::new(),
+        };
+
+        uevent
+            .lines()
+            .filter_map(|line| {
+                let (key, value) = line.split_once('=')?;
+                Some((key.to_owned(), value.to_owned()))
+            })
+            .collect()
+    }
+
+    /// Returns an iterator over the attributes in a subdirectory of the device, such as `queue`
+    /// or `power`.
+    ///
+    /// libudev only lists the attributes at the top level of the device, so the subdirectory is
+    /// read from sysfs directly. Each item is the attribute name relative to the device, e.g.
+    /// `queue/rotational`, together with its value as returned by
+    /// [`attribute_value`][Self::attribute_value].
+    ///
+    /// The `subpath` parameter must be a non-empty relative path below the device's syspath,
+    /// otherwise `EINVAL` is returned.
+    ///
+    /// ## Example
+    ///
+    /// This example prints out all of a disk's request queue parameters:
+    ///
+    /// ```no_run
+    /// # use std::path::Path;
+    /// # let device = udev::Device::from_syspath(Path::new("/sys/block/sda")).unwrap();
+    /// for (name, value) in device.nested_attributes("queue").unwrap() {
+    ///     println!("{:?} = {:?}", name, value);
+    /// }
+    /// ```
+    pub fn nested_attributes<T: AsRef<OsStr>>(
+        &self,
+        subpath: T,
+    ) -> Result<impl Iterator<Item = (OsString, Option<&OsStr>)>> {
+        let subpath = Path::new(subpath.as_ref());
+
+        let path = self
+            .attribute_path(subpath.as_os_str())
+            .ok_or_else(|| std::io::Error::from_raw_os_error(libc::EINVAL))?;
+
+        let mut names = Vec::new();
+
+        for entry in fs::read_dir(path)? {
+            let entry = entry?;
+
+            if entry.file_type()?.is_file() {
+                names.push(subpath.join(entry.file_name()).into_os_string());
+            }
+        }
+
+        names.sort();
+
+        Ok(names.into_iter().map(move |name| {
+            let value = self.attribute_value(&name);
+            (name, value)
+        }))
+    }
+
     /// Returns the device action for the device.
     pub fn action(&self) -> Option<&OsStr> {
         unsafe { util::ptr_to_os_str(ffi::udev_device_get_action(self.device)) }
     }
+
+    /// Returns the device action for the device as an `EventType`.
+    ///
+    /// Only devices received from a monitor have an action; for other devices, such as those from
+    /// an `Enumerator`, this returns `None`. An action that isn't known to this crate is returned
+    /// as `Some(EventType::Unknown)`.
+    pub fn action_type(&self) -> Option<EventType> {
+        self.action().map(EventType::from_action)
+    }
+
+    /// Returns the sequence number of the kernel event the device was created from.
+    ///
+    /// Devices received from a monitor or created by `from_environment()` carry the `SEQNUM` of
+    /// their event, which orders them relative to the events received from a monitor. Devices
+    /// read from sysfs, such as those from an `Enumerator`, have none, and this returns `None`.
+    /// For events, `Event::sequence_number()` returns the number directly.
+    pub fn sequence_number(&self) -> Option<u64> {
+        match unsafe { ffi::udev_device_get_seqnum(self.device) } {
+            0 => None,
+            seqnum => Some(seqnum),
+        }
+    }
+
+    /// Returns the serial number of the device, if udev knows it.
+    ///
+    /// This is the `ID_SERIAL_SHORT` property, falling back to `ID_SERIAL`. Unlike the syspath
+    /// or sysname, the serial number stays the same across reboots and when the device is
+    /// plugged into a different port.
+    pub fn serial(&self) -> Option<&OsStr> {
+        self.property_value("ID_SERIAL_SHORT")
+            .or_else(|| self.property_value("ID_SERIAL"))
+    }
+
+    /// Returns the most stable identifier udev knows for the device, for use as a persistent key.
+    ///
+    /// In order of preference, this is:
+    ///
+    /// - `ID_PATH`, the path through the buses the device is attached by, e.g.
+    ///   `pci-0000:00:14.0-usb-0:2:1.0`. It stays the same across reboots as long as the device
+    ///   stays in the same port, and tells identical devices apart.
+    /// - `ID_SERIAL`, the vendor, model and serial number reported by the device. It follows the
+    ///   device from port to port, but identical devices without a serial number share it.
+    ///
+    /// Returns `None` if the device has neither, which is the case for most virtual devices. The
+    /// syspath is not a fallback, as the kernel may number devices differently on every boot.
+    pub fn stable_id(&self) -> Option<OsString> {
+        self.property_value("ID_PATH")
+            .or_else(|| self.property_value("ID_SERIAL"))
+            .map(OsStr::to_owned)
+    }
+
+    /// Returns a best-effort label for showing the device to a user, e.g.
+    /// `USB: FTDI FT232R USB UART (/dev/ttyUSB0)`.
+    ///
+    /// The label is made of three parts:
+    ///
+    /// - The bus from the `ID_BUS` property in uppercase, e.g. `USB` or `ATA`, or else the
+    ///   subsystem, e.g. `input`. It's left out, with the colon, if the device has neither.
+    /// - The vendor and model names from the hwdb (`ID_VENDOR_FROM_DATABASE` and
+    ///   `ID_MODEL_FROM_DATABASE`), or else as reported by the device (`ID_VENDOR` and
+    ///   `ID_MODEL`, with udev's `_` placeholders turned back into spaces). If neither is known,
+    ///   the sysname is used instead.
+    /// - The device node in parentheses, if the device has one.
+    ///
+    /// The format is meant for humans and may change; don't parse it.
+    pub fn label(&self) -> String {
+        let name = |property: &str| {
+            self.property_value(format!("{}_FROM_DATABASE", property))
+                .map(|name| name.to_string_lossy().into_owned())
+                .or_else(|| {
+                    self.property_value(property)
+                        .map(|name| name.to_string_lossy().replace('_', " "))
+                })
+        };
+
+        let kind = match self.property_value("ID_BUS") {
+            Some(bus) => Some(bus.to_string_lossy().to_uppercase()),
+            None => self
+                .subsystem()
+                .map(|subsystem| subsystem.to_string_lossy().into_owned()),
+        };
+
+        let mut label = kind.map(|kind| format!("{}: ", kind)).unwrap_or_default();
+
+        match (name("ID_VENDOR"), name("ID_MODEL")) {
+            (Some(vendor), Some(model)) => label.push_str(&format!("{} {}", vendor, model)),
+            (Some(name), None) | (None, Some(name)) => label.push_str(&name),
+            (None, None) => label.push_str(&self.sysname().to_string_lossy()),
+        }
+
+        if let Some(devnode) = self.devnode() {
+            label.push_str(&format!(" ({})", devnode.display()));
+        }
+
+        label
+    }
+
+    /// Checks whether both devices have the same serial number.
+    ///
+    /// Returns `false` if either device has no serial number.
+    pub fn same_physical_device(&self, other: &Device) -> bool {
+        match (self.serial(), other.serial()) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns the human-readable name of the device's vendor.
+    ///
+    /// The name is looked up in the hardware database using the device's modalias, falling back
+    /// to the `ID_VENDOR` property if the database has no entry for the device.
+    #[cfg(feature = "hwdb")]
+    pub fn vendor_name(&self, hwdb: &Hwdb) -> Option<OsString> {
+        self.hwdb_name(hwdb, "ID_VENDOR_FROM_DATABASE", "ID_VENDOR")
+    }
+
+    /// Returns the human-readable name of the device's model.
+    ///
+    /// The name is looked up in the hardware database using the device's modalias, falling back
+    /// to the `ID_MODEL` property if the database has no entry for the device.
+    #[cfg(feature = "hwdb")]
+    pub fn model_name(&self, hwdb: &Hwdb) -> Option<OsString> {
+        self.hwdb_name(hwdb, "ID_MODEL_FROM_DATABASE", "ID_MODEL")
+    }
+
+    #[cfg(feature = "hwdb")]
+    fn hwdb_name(&self, hwdb: &Hwdb, key: &str, fallback: &str) -> Option<OsString> {
+        self.modalias()
+            .and_then(|modalias| hwdb.query_one(modalias, OsStr::new(key)))
+            .or_else(|| self.property_value(fallback))
+            .map(OsStr::to_owned)
+    }
 }
 
 impl<'a> Iterator for Attributes<'a> {
@@ -550,3 +1630,85 @@ impl<'a> Iterator for Attributes<'a> {
         (0, None)
     }
 }
+
+impl<'a> FusedIterator for Attributes<'a> {}
+
+impl<'a> Iterator for DevLinks<'a> {
+    type Item = &'a Path;
+
+    // Devlinks are list entries without values, named by the path of the symlink.
+    fn next(&mut self) -> Option<&'a Path> {
+        self.entries.next().map(|devlink| Path::new(devlink.name))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<'a> FusedIterator for DevLinks<'a> {}
+
+impl<'a> Iterator for Tags<'a> {
+    type Item = &'a OsStr;
+
+    // Tags are list entries without values, named by the tag.
+    fn next(&mut self) -> Option<&'a OsStr> {
+        self.entries.next().map(|tag| tag.name)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<'a> FusedIterator for Tags<'a> {}
+
+impl Iterator for Ancestors {
+    type Item = Device;
+
+    fn next(&mut self) -> Option<Device> {
+        let device = self.next.take()?;
+        self.next = device.parent();
+
+        Some(device)
+    }
+}
+
+// Once the root is reached, `next` stays `None`.
+impl FusedIterator for Ancestors {}
+
+/// Creates devices that all share one `Udev` context.
+///
+/// Each call to a convenience constructor like `Device::from_syspath()` looks up a context first,
+/// which costs a new context when the `send` feature is enabled. When constructing many devices,
+/// a factory avoids that: it holds a context and passes it to
+/// `Device::from_syspath_with_context()`. See the `device_construction` benchmark.
+///
+/// Cloning a factory only adds a reference to its context. Like `Udev`, it is `Send` with the
+/// `send` feature enabled.
+#[derive(Clone)]
+pub struct DeviceFactory {
+    udev: Udev,
+}
+
+impl DeviceFactory {
+    /// Creates a factory with a new context.
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_udev(Udev::new()?))
+    }
+
+    /// Creates a factory using an existing `Udev` instance.
+    pub fn with_udev(udev: Udev) -> Self {
+        Self { udev }
+    }
+
+    /// Creates a device for a given syspath, using the factory's context.
+    pub fn device<P: AsRef<Path>>(&self, syspath: P) -> Result<Device> {
+        Device::from_syspath_with_context(self.udev.clone(), syspath)
+    }
+
+    /// Returns the factory's context.
+    pub fn udev(&self) -> &Udev {
+        &self.udev
+    }
+}
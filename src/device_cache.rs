@@ -0,0 +1,251 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::io::Result;
+use std::path::{Path, PathBuf};
+
+use Udev;
+
+use {Device, Event};
+
+/// Remembers devices by syspath, forgetting them again when a monitor event says they changed.
+///
+/// Interactive tools often look up the same devices over and over while also monitoring them.
+/// The first lookup of a syspath reads the device from sysfs and the udev database, and later
+/// lookups return the same device until an event for it is passed to `apply_event()`. All devices
+/// share the cache's `Udev` context.
+///
+/// Devices are cached by their own `syspath()`, which is below `/sys/devices`. Looking devices up
+/// by a symlink, like `/sys/class/net/eth0`, works, but reads the device every time.
+///
+/// ## Example
+///
+/// ```no_run
+/// let mut cache = udev::DeviceCache::new().unwrap();
+/// let socket = udev::MonitorBuilder::new().unwrap().listen().unwrap();
+///
+/// let tty = cache.get("/sys/devices/virtual/tty/tty0").unwrap();
+/// println!("{:?}", tty.property_value("ID_PATH"));
+///
+/// for event in socket.iter() {
+///     cache.apply_event(&event);
+/// }
+/// ```
+pub struct DeviceCache {
+    udev: Udev,
+    devices: HashMap<PathBuf, Device>,
+}
+
+impl DeviceCache {
+    /// Creates an empty cache with a new context.
+    pub fn new() -> Result<Self> {
+        Ok(Self::with_udev(Udev::new()?))
+    }
+
+    /// Creates an empty cache using an existing `Udev` instance.
+    pub fn with_udev(udev: Udev) -> Self {
+        Self {
+            udev,
+            devices: HashMap::new(),
+        }
+    }
+
+    /// Returns the device with the given syspath, reading it only if it isn't cached.
+    pub fn get<P: AsRef<Path>>(&mut self, syspath: P) -> Result<Device> {
+        let syspath = syspath.as_ref();
+
+        if let Some(device) = self.devices.get(syspath) {
+            return Ok(device.clone());
+        }
+
+        let device = Device::from_syspath_with_context(self.udev.clone(), syspath)?;
+
+        Ok(self
+            .devices
+            .entry(device.syspath().to_owned())
+            .or_insert(device)
+            .clone())
+    }
+
+    /// Forgets the device the event is about, so the next lookup reads it again.
+    ///
+    /// Every kind of event invalidates the device: after `add`, `change`, `bind` or `unbind` its
+    /// properties may have changed, and after `remove` it's gone. When a device is renamed, the
+    /// event carries its new syspath, and the device is forgotten under its old one as well.
+    ///
+    /// Returns whether a cached device was forgotten.
+    pub fn apply_event(&mut self, event: &Event) -> bool {
+        self.invalidate(event)
+    }
+
+    /// Forgets the given device, under its current syspath and, if it was renamed, its old one.
+    fn invalidate(&mut self, device: &Device) -> bool {
+        let mut removed = self.devices.remove(device.syspath()).is_some();
+
+        // Like `DEVPATH`, `DEVPATH_OLD` doesn't include the sysfs mount point.
+        if let Some(old) = device.property_value("DEVPATH_OLD") {
+            let mut old_syspath = OsString::from("/sys");
+            old_syspath.push(old);
+
+            removed |= self.devices.remove(Path::new(&old_syspath)).is_some();
+        }
+
+        removed
+    }
+
+    /// Returns whether a device with the given syspath is cached.
+    pub fn contains<P: AsRef<Path>>(&self, syspath: P) -> bool {
+        self.devices.contains_key(syspath.as_ref())
+    }
+
+    /// Returns the number of cached devices.
+    pub fn len(&self) -> usize {
+        self.devices.len()
+    }
+
+    /// Returns whether no devices are cached.
+    pub fn is_empty(&self) -> bool {
+        self.devices.is_empty()
+    }
+
+    /// Forgets all cached devices.
+    pub fn clear(&mut self) {
+        self.devices.clear();
+    }
+
+    /// Returns the context shared by the cached devices.
+    pub fn udev(&self) -> &Udev {
+        &self.udev
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::ffi::OsStr;
+    use std::process::Command;
+    use {AsRaw, AsRawWithContext};
+
+    const TTY0: &str = "/sys/devices/virtual/tty/tty0";
+    const NULL: &str = "/sys/devices/virtual/mem/null";
+
+    #[test]
+    fn get_is_memoized() {
+        let mut cache = DeviceCache::new().unwrap();
+
+        let first = cache.get(TTY0).unwrap();
+        let second = cache.get(TTY0).unwrap();
+
+        assert_eq!(first.as_raw(), second.as_raw());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn devices_share_context() {
+        let mut cache = DeviceCache::new().unwrap();
+
+        let tty = cache.get(TTY0).unwrap();
+        let null = cache.get(NULL).unwrap();
+
+        assert_eq!(tty.udev().as_raw(), cache.udev().as_raw());
+        assert_eq!(null.udev().as_raw(), cache.udev().as_raw());
+    }
+
+    #[test]
+    fn apply_event_forgets_device() {
+        let mut cache = DeviceCache::new().unwrap();
+
+        let first = cache.get(TTY0).unwrap();
+        let event = Event::from_device(first.clone());
+
+        assert!(cache.apply_event(&event));
+        assert!(!cache.contains(TTY0));
+        assert!(!cache.apply_event(&event));
+
+        let second = cache.get(TTY0).unwrap();
+
+        assert_ne!(first.as_raw(), second.as_raw());
+        assert_eq!(first.syspath(), second.syspath());
+    }
+
+    #[test]
+    fn apply_event_keeps_other_devices() {
+        let mut cache = DeviceCache::new().unwrap();
+
+        let tty = cache.get(TTY0).unwrap();
+        cache.get(NULL).unwrap();
+
+        cache.apply_event(&Event::from_device(tty));
+
+        assert!(cache.contains(NULL));
+        assert_eq!(cache.len(), 1);
+    }
+
+    // Applies an event with the given properties to a cache holding `TTY0` and `NULL`, by running
+    // `apply_event_from_environment` in a child process. Events can only be built from the
+    // environment, the way udev passes them to the programs it runs, and libudev parses the
+    // environment in place, so each process can only build one.
+    fn apply_event_in_child(properties: &[(&str, &str)]) {
+        let output = Command::new(env::current_exe().unwrap())
+            .args(&[
+                "--exact",
+                "device_cache::tests::apply_event_from_environment",
+                "--ignored",
+                "--test-threads=1",
+            ])
+            .envs(properties.iter().cloned())
+            .env("EXPECTED_ACTION", properties[0].1)
+            .output()
+            .unwrap();
+
+        assert!(
+            output.status.success(),
+            "{}",
+            String::from_utf8_lossy(&output.stdout)
+        );
+    }
+
+    #[test]
+    #[ignore = "run by the other tests, with an event in the environment"]
+    fn apply_event_from_environment() {
+        // Read before libudev mangles the environment.
+        let expected_action = match env::var("EXPECTED_ACTION") {
+            Ok(action) => action,
+            Err(_) => return,
+        };
+
+        let mut cache = DeviceCache::new().unwrap();
+        cache.get(TTY0).unwrap();
+        cache.get(NULL).unwrap();
+
+        let event = Event::from_device(Device::from_environment().unwrap());
+        assert_eq!(event.action(), Some(OsStr::new(&expected_action)));
+
+        assert!(cache.apply_event(&event));
+        assert!(!cache.contains(TTY0));
+        assert!(cache.contains(NULL));
+    }
+
+    #[test]
+    fn apply_event_forgets_device_for_every_action() {
+        for &action in ["add", "remove", "change"].iter() {
+            apply_event_in_child(&[
+                ("ACTION", action),
+                ("DEVPATH", "/devices/virtual/tty/tty0"),
+                ("SUBSYSTEM", "tty"),
+                ("SEQNUM", "1"),
+            ]);
+        }
+    }
+
+    #[test]
+    fn apply_event_forgets_renamed_device() {
+        apply_event_in_child(&[
+            ("ACTION", "move"),
+            ("DEVPATH", "/devices/virtual/tty/tty-renamed"),
+            ("DEVPATH_OLD", "/devices/virtual/tty/tty0"),
+            ("SUBSYSTEM", "tty"),
+            ("SEQNUM", "1"),
+        ]);
+    }
+}
@@ -1,10 +1,12 @@
 use std::ffi::OsStr;
 use std::io::Result;
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
 use std::path::Path;
 
+use list::{EntryList, List};
 use Udev;
-use {ffi, list::List, util};
+use {ffi, util};
 
 use {AsRaw, AsRawWithContext, Device, FromRaw};
 
@@ -13,6 +15,10 @@ use {AsRaw, AsRawWithContext, Device, FromRaw};
 /// An Enumerator scans `/sys` for devices matching its filters. Filters are added to an Enumerator
 /// by calling its `match_*` and `nomatch_*` methods. After the filters are setup, the
 /// `scan_devices()` method finds devices in `/sys` that match the filters.
+///
+/// Unless `match_is_initialized()` is called, or the enumerator is created with
+/// `new_initialized()`, the results include devices that udev has not finished processing yet.
+/// Such devices may be missing most of their properties.
 pub struct Enumerator {
     udev: Udev,
     enumerator: *mut ffi::udev_enumerate,
@@ -42,14 +48,15 @@ as_ffi_with_context!(
     Enumerator,
     enumerator,
     ffi::udev_enumerate,
-    ffi::udev_enumerate_ref
+    ffi::udev_enumerate_ref,
+    ffi::udev_enumerate_get_udev
 );
 
 impl Enumerator {
     /// Creates a new Enumerator.
     pub fn new() -> Result<Self> {
-        // Create a new Udev context for this enumeration
-        let udev = Udev::new()?;
+        // Reuse this thread's Udev context
+        let udev = Udev::cached()?;
         Self::with_udev(udev)
     }
 
@@ -62,7 +69,26 @@ impl Enumerator {
         })
     }
 
+    /// Creates a new `Enumerator` that matches only initialized devices.
+    ///
+    /// This is equivalent to calling `match_is_initialized()` on a new `Enumerator`, and is what
+    /// most callers want.
+    pub fn new_initialized() -> Result<Self> {
+        Self::with_udev_initialized(Udev::cached()?)
+    }
+
+    /// Creates a new `Enumerator` that matches only initialized devices, with an existing `Udev`
+    /// instance.
+    pub fn with_udev_initialized(udev: Udev) -> Result<Self> {
+        let mut enumerator = Self::with_udev(udev)?;
+        enumerator.match_is_initialized()?;
+        Ok(enumerator)
+    }
+
     /// Adds a filter that matches only initialized devices.
+    ///
+    /// Without this filter, devices that udev is still processing are included as well, and their
+    /// properties may be incomplete.
     pub fn match_is_initialized(&mut self) -> Result<()> {
         util::errno_to_result(unsafe {
             ffi::udev_enumerate_add_match_is_initialized(self.enumerator)
@@ -106,6 +132,9 @@ impl Enumerator {
     }
 
     /// Adds a filter that matches only devices with the given property value.
+    ///
+    /// The value is matched as a shell-style glob, so it may contain `*`, `?` and `[...]`. Use
+    /// `match_property_glob()` to make that explicit.
     pub fn match_property<T: AsRef<OsStr>, U: AsRef<OsStr>>(
         &mut self,
         property: T,
@@ -123,7 +152,24 @@ impl Enumerator {
         })
     }
 
+    /// Adds a filter that matches only devices whose property value matches the given
+    /// shell-style glob pattern, e.g. `*Intel*`.
+    ///
+    /// libudev matches property values with `fnmatch(3)`, so this behaves exactly like
+    /// `match_property()`; it exists to make the glob semantics explicit at the call site.
+    pub fn match_property_glob<T: AsRef<OsStr>, U: AsRef<OsStr>>(
+        &mut self,
+        property: T,
+        pattern: U,
+    ) -> Result<()> {
+        self.match_property(property, pattern)
+    }
+
     /// Adds a filter that matches only devices with the given tag.
+    ///
+    /// When called several times, only devices that have all of the tags are matched. Older
+    /// versions of libudev, like the one in systemd 215, match devices that have any of the tags
+    /// instead.
     pub fn match_tag<T: AsRef<OsStr>>(&mut self, tag: T) -> Result<()> {
         let tag = util::os_str_to_cstring(tag)?;
 
@@ -132,6 +178,19 @@ impl Enumerator {
         })
     }
 
+    /// Adds a filter that matches only devices that have all of the given tags, e.g. both `seat`
+    /// and `uaccess`.
+    ///
+    /// This is the same as calling `match_tag()` for each tag, so the caveat about older versions
+    /// of libudev applies.
+    pub fn match_all_tags<T: AsRef<OsStr>>(&mut self, tags: &[T]) -> Result<()> {
+        for tag in tags {
+            self.match_tag(tag)?;
+        }
+
+        Ok(())
+    }
+
     /// Includes the parent device and all devices in the subtree of the parent device.
     pub fn match_parent(&mut self, parent: &Device) -> Result<()> {
         util::errno_to_result(unsafe {
@@ -167,8 +226,8 @@ impl Enumerator {
     }
 
     /// Includes the device with the given syspath.
-    pub fn add_syspath<T: AsRef<OsStr>>(&mut self, syspath: T) -> Result<()> {
-        let syspath = util::os_str_to_cstring(syspath)?;
+    pub fn add_syspath<P: AsRef<Path>>(&mut self, syspath: P) -> Result<()> {
+        let syspath = util::os_str_to_cstring(syspath.as_ref())?;
 
         util::errno_to_result(unsafe {
             ffi::udev_enumerate_add_syspath(self.enumerator, syspath.as_ptr())
@@ -177,15 +236,107 @@ impl Enumerator {
 
     /// Scans `/sys` for devices matching the attached filters.
     ///
-    /// The devices will be sorted in dependency order.
+    /// The devices will be sorted in dependency order. Each syspath is included at most once, even
+    /// if it was added by several overlapping `add_syspath()` calls or filters.
+    ///
+    /// libudev always sorts the scanned devices and offers no way to skip it. The sort is cheap
+    /// compared to reading sysfs and constructing the devices, though; see the `scan_devices`
+    /// benchmark.
+    ///
+    /// Each device is constructed only when the iterator reaches it, with the enumerator's `Udev`
+    /// context. Devices that disappear between the scan and their construction are skipped.
     pub fn scan_devices(&mut self) -> Result<List<Enumerator, Device>> {
         util::errno_to_result(unsafe { ffi::udev_enumerate_scan_devices(self.enumerator) })?;
 
         Ok(Devices {
             entry: unsafe { ffi::udev_enumerate_get_list_entry(self.enumerator) },
+            udev: Some(self.udev.clone()),
             phantom: PhantomData,
         })
     }
+
+    /// Scans `/sys` for devices matching the attached filters, constructing each device only when
+    /// the iterator reaches it.
+    ///
+    /// This is the same as `scan_devices()`: only the list of syspaths is held in memory, so
+    /// iterating uses O(1) memory regardless of the number of devices, as long as the caller
+    /// doesn't keep the devices around.
+    pub fn devices_lazy(&mut self) -> Result<Devices<'_>> {
+        self.scan_devices()
+    }
+
+    /// Scans `/sys` for devices matching the attached filters, returning them sorted by the given
+    /// key function.
+    ///
+    /// Unlike `scan_devices()`, all devices are created up front. The sort is stable, so devices
+    /// with equal keys stay in dependency order.
+    pub fn scan_devices_sorted_by<F, K>(&mut self, key: F) -> Result<Vec<Device>>
+    where
+        F: FnMut(&Device) -> K,
+        K: Ord,
+    {
+        let mut devices: Vec<Device> = self.scan_devices()?.collect();
+        devices.sort_by_key(key);
+        Ok(devices)
+    }
+
+    /// Scans `/sys` for devices matching the attached filters, keeping those for which
+    /// `predicate` returns `true`.
+    ///
+    /// The filters are applied by libudev, which only supports exact and glob matches. The
+    /// predicate refines the results in Rust, e.g. to find batteries below 20% capacity:
+    ///
+    /// ```no_run
+    /// let mut enumerator = udev::Enumerator::new().unwrap();
+    /// enumerator.match_subsystem("power_supply").unwrap();
+    ///
+    /// let low = enumerator
+    ///     .scan_devices_filtered(|device| {
+    ///         device
+    ///             .as_power_supply()
+    ///             .and_then(|supply| supply.capacity_percent())
+    ///             .map_or(false, |capacity| capacity < 20)
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    /// Narrow the scan with filters where possible, as every matching device is created before
+    /// the predicate sees it. The devices are returned in dependency order.
+    pub fn scan_devices_filtered<F>(&mut self, mut predicate: F) -> Result<Vec<Device>>
+    where
+        F: FnMut(&Device) -> bool,
+    {
+        Ok(self
+            .scan_devices()?
+            .filter(|device| predicate(device))
+            .collect())
+    }
+
+    /// Creates the device with the given syspath, checking that it matches the attached filters.
+    ///
+    /// This scans `/sys` in the same way as `scan_devices()` and fails with an error of kind
+    /// `InvalidInput` if the device is not part of the results. Errors looking up the device
+    /// itself, e.g. because the syspath does not exist, are returned as-is.
+    pub fn device_at<P: AsRef<Path>>(&mut self, syspath: P) -> Result<Device> {
+        let device = Device::from_syspath_with_context(self.udev.clone(), syspath)?;
+
+        util::errno_to_result(unsafe { ffi::udev_enumerate_scan_devices(self.enumerator) })?;
+
+        let mut entries: EntryList<Enumerator> = EntryList {
+            entry: unsafe { ffi::udev_enumerate_get_list_entry(self.enumerator) },
+            udev: None,
+            phantom: PhantomData,
+        };
+
+        if entries.any(|entry| entry.name() == device.syspath()) {
+            Ok(device)
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "device does not match the enumerator's filters",
+            ))
+        }
+    }
 }
 
 /// Iterator over devices.
@@ -202,7 +353,13 @@ impl<'a> Iterator for Devices<'a> {
 
             self.entry = unsafe { ffi::udev_list_entry_get_next(self.entry) };
 
-            match Device::from_syspath(syspath) {
+            let device = match self.udev {
+                Some(ref udev) => Device::from_syspath_with_context(udev.clone(), syspath),
+                None => Device::from_syspath(syspath),
+            };
+
+            // Devices may disappear between the scan and the lookup; skip them.
+            match device {
                 Ok(d) => return Some(d),
                 Err(_) => continue,
             };
@@ -216,6 +373,8 @@ impl<'a> Iterator for Devices<'a> {
     }
 }
 
+impl<'a> FusedIterator for Devices<'a> {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -239,6 +398,18 @@ mod tests {
         let _ = enumerator.scan_devices().unwrap().collect::<Vec<_>>();
     }
 
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "paired with a udev context it wasn't created with")]
+    fn round_trip_with_wrong_context() {
+        let enumerator = Enumerator::with_udev(Udev::new().unwrap()).unwrap();
+        let other = Udev::new().unwrap();
+
+        let (_, ptr) = enumerator.into_raw_with_context();
+
+        unsafe { Enumerator::from_raw_with_context(other.into_raw(), ptr) };
+    }
+
     #[test]
     fn test_enumeration() {
         fn find_hidraws(en: &mut Enumerator) -> Devices<'_> {
@@ -253,6 +424,59 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_scan_devices_dedups_syspaths() {
+        let mut en = Enumerator::new().unwrap();
+        let tty0 = Device::from_syspath("/sys/devices/virtual/tty/tty0").unwrap();
+
+        en.match_parent(&tty0).unwrap();
+        en.add_syspath(tty0.syspath()).unwrap();
+        en.add_syspath(tty0.syspath()).unwrap();
+
+        let syspaths: Vec<_> = en
+            .scan_devices()
+            .unwrap()
+            .map(|dev| dev.syspath().to_owned())
+            .collect();
+        let unique: std::collections::HashSet<_> = syspaths.iter().collect();
+
+        assert!(syspaths.iter().any(|syspath| syspath == tty0.syspath()));
+        assert_eq!(syspaths.len(), unique.len());
+    }
+
+    #[test]
+    fn test_enumerated_devices_have_no_action() {
+        let mut en = Enumerator::new().unwrap();
+
+        for dev in en.scan_devices().unwrap() {
+            assert_eq!(dev.action(), None);
+            assert_eq!(dev.action_type(), None);
+        }
+    }
+
+    #[test]
+    fn test_match_property_glob() {
+        let mut en = Enumerator::new().unwrap();
+        en.match_property_glob("SUBSYSTEM", "t?[xy]").unwrap();
+
+        for dev in en.scan_devices().unwrap() {
+            assert_eq!(dev.property_value("SUBSYSTEM").unwrap(), "tty");
+        }
+    }
+
+    #[test]
+    fn test_match_all_tags() {
+        let mut en = Enumerator::new().unwrap();
+        en.match_all_tags(&["seat", "uaccess"]).unwrap();
+
+        for dev in en.scan_devices().unwrap() {
+            let tags = dev.tags_vec();
+
+            assert!(tags.iter().any(|tag| tag == "seat"));
+            assert!(tags.iter().any(|tag| tag == "uaccess"));
+        }
+    }
+
     // The above test which limits devices to `hidraw` did not reproduce the crash on libudev 215
     // caused by the use of a bogus udev context.  Clearly it's important to test all enumeration
     // pathways.
@@ -0,0 +1,102 @@
+use std::io::Result;
+use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::ptr;
+use std::time::Duration;
+
+use io_lifetimes::OwnedFd;
+
+/// A minimal wrapper around an epoll instance, used to wait on several monitor sockets at once.
+///
+/// File descriptors are registered level-triggered for readability, so the epoll file descriptor
+/// itself stays readable for as long as any registered file descriptor is.
+pub(crate) struct Epoll {
+    fd: OwnedFd,
+}
+
+impl Epoll {
+    /// Creates a new epoll instance.
+    pub(crate) fn new() -> Result<Self> {
+        let fd = unsafe { libc::epoll_create1(libc::EPOLL_CLOEXEC) };
+
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(Self {
+            fd: unsafe { OwnedFd::from_raw_fd(fd) },
+        })
+    }
+
+    /// Registers `fd` for readability, reporting it with the given token.
+    pub(crate) fn add(&self, fd: RawFd, token: u64) -> Result<()> {
+        let mut event = libc::epoll_event {
+            events: libc::EPOLLIN as u32,
+            u64: token,
+        };
+
+        let ret =
+            unsafe { libc::epoll_ctl(self.fd.as_raw_fd(), libc::EPOLL_CTL_ADD, fd, &mut event) };
+
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Unregisters `fd`.
+    pub(crate) fn remove(&self, fd: RawFd) -> Result<()> {
+        let ret = unsafe {
+            libc::epoll_ctl(
+                self.fd.as_raw_fd(),
+                libc::EPOLL_CTL_DEL,
+                fd,
+                ptr::null_mut(),
+            )
+        };
+
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(())
+    }
+
+    /// Waits until at least one registered file descriptor is ready, or until `timeout` has
+    /// elapsed, returning the tokens of the ready file descriptors.
+    ///
+    /// Waits indefinitely if `timeout` is `None`.
+    pub(crate) fn wait(&self, timeout: Option<Duration>) -> Result<Vec<u64>> {
+        let mut events = [libc::epoll_event { events: 0, u64: 0 }; 16];
+
+        // Round up, so we don't wake up just before the timeout and spin.
+        let millis = match timeout {
+            Some(timeout) => timeout
+                .as_micros()
+                .div_ceil(1000)
+                .min(libc::c_int::MAX as u128) as libc::c_int,
+            None => -1,
+        };
+
+        let n = unsafe {
+            libc::epoll_wait(
+                self.fd.as_raw_fd(),
+                events.as_mut_ptr(),
+                events.len() as libc::c_int,
+                millis,
+            )
+        };
+
+        if n < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        Ok(events[..n as usize].iter().map(|event| event.u64).collect())
+    }
+}
+
+impl AsRawFd for Epoll {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
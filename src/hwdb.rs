@@ -1,13 +1,17 @@
-use std::ffi::{CString, OsStr};
+use std::collections::HashMap;
+use std::ffi::{CString, OsStr, OsString};
 use std::io::Result;
 use std::marker::PhantomData;
 use std::os::unix::ffi::OsStrExt;
+use std::sync::{Arc, Mutex};
 
 use libc::c_char;
 
 use ffi;
-use list::EntryList;
-use FromRaw;
+use list::{EntryList, OwnedEntry};
+use Udev;
+
+use {AsRaw, FromRaw, FromRawWithContext};
 
 /// Rust wrapper for the `udev_hwdb` struct, which provides access to `udev`'s
 /// hardware database API.
@@ -15,12 +19,17 @@ use FromRaw;
 /// Like the `udev` struct, `udev_hwdb` is refcounted and automatically managed
 /// by the Rust wrapper.
 pub struct Hwdb {
+    // libudev doesn't use the context after creating the hwdb, so one from `from_raw()` has none.
+    udev: Option<Udev>,
     hwdb: *mut ffi::udev_hwdb,
 }
 
 impl Clone for Hwdb {
     fn clone(&self) -> Self {
-        unsafe { Self::from_raw(ffi::udev_hwdb_ref(self.hwdb)) }
+        Self {
+            udev: self.udev.clone(),
+            hwdb: unsafe { ffi::udev_hwdb_ref(self.hwdb) },
+        }
     }
 }
 
@@ -35,17 +44,44 @@ unsafe impl Send for Hwdb {}
 #[cfg(feature = "sync")]
 unsafe impl Sync for Hwdb {}
 
-as_ffi!(Hwdb, hwdb, ffi::udev_hwdb, ffi::udev_hwdb_ref);
+// `AsRawWithContext` isn't implemented, as its `as_raw()` would be ambiguous with `AsRaw`'s.
+as_raw!(Hwdb, hwdb, ffi::udev_hwdb, ffi::udev_hwdb_ref);
+
+impl FromRaw<ffi::udev_hwdb> for Hwdb {
+    /// Creates a `Hwdb` from a raw pointer, without a `Udev` context.
+    ///
+    /// libudev only uses the context to create a hwdb, so the hwdb works the same without one. Use
+    /// `from_raw_with_context()` to keep a context alive alongside it.
+    unsafe fn from_raw(hwdb: *mut ffi::udev_hwdb) -> Self {
+        Self { udev: None, hwdb }
+    }
+}
+
+impl FromRawWithContext<ffi::udev_hwdb> for Hwdb {
+    unsafe fn from_raw_with_context(udev: *mut ffi::udev, hwdb: *mut ffi::udev_hwdb) -> Self {
+        Self {
+            udev: Some(Udev::from_raw(udev)),
+            hwdb,
+        }
+    }
+}
 
 impl Hwdb {
     /// Creates a new Hwdb context.
     pub fn new() -> Result<Self> {
-        // NOTE: udev_hwdb_new states that its first parameter is unused.
-        // However, older versions of udev check it against NULL, so we can't just pass an
-        // empty pointer in. Instead, we pass in a garbage pointer.
-        let junk: *mut ffi::udev = 0x41414141_41414141 as *mut ffi::udev;
-        let ptr = try_alloc!(unsafe { ffi::udev_hwdb_new(junk) });
-        Ok(unsafe { Self::from_raw(ptr) })
+        // Reuse this thread's Udev context
+        Self::with_context(Udev::cached()?)
+    }
+
+    /// Creates a new Hwdb context with an existing `Udev` instance.
+    pub fn with_context(udev: Udev) -> Result<Self> {
+        // NOTE: udev_hwdb_new states that its first parameter is unused. However, older versions
+        // of udev check it against NULL, so we pass in a real context.
+        let ptr = try_alloc!(unsafe { ffi::udev_hwdb_new(udev.as_raw()) });
+        Ok(Self {
+            udev: Some(udev),
+            hwdb: ptr,
+        })
     }
 
     /// Queries the hardware database with the given `modalias` query,
@@ -62,6 +98,7 @@ impl Hwdb {
                     0,
                 )
             },
+            udev: None,
             phantom: PhantomData,
         }
     }
@@ -72,12 +109,98 @@ impl Hwdb {
             .find(|e| e.name == name.as_ref())
             .map(|e| e.value.unwrap_or_else(|| OsStr::new("")))
     }
+
+    /// Returns whether the given `modalias` query yields an entry with the given name.
+    pub fn contains_property<S: AsRef<OsStr>>(&self, modalias: S, name: S) -> bool {
+        self.query(modalias).any(|e| e.name == name.as_ref())
+    }
+}
+
+/// A `Hwdb` that remembers the results of its queries.
+///
+/// Tools that label many devices often query the same modaliases over and over, e.g. for a
+/// hundred identical USB devices. The first query for a modalias reads the database, and later
+/// queries for it return the same shared results. The database is not watched for changes, so
+/// create a new `CachedHwdb` to pick up an updated database.
+pub struct CachedHwdb {
+    hwdb: Hwdb,
+    cache: Mutex<HashMap<OsString, Arc<Vec<OwnedEntry>>>>,
+}
+
+impl CachedHwdb {
+    /// Creates a cache in front of `hwdb`.
+    pub fn new(hwdb: Hwdb) -> Self {
+        Self {
+            hwdb,
+            cache: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Queries the hardware database with the given `modalias` query, returning the matching
+    /// entries.
+    ///
+    /// The database is only read the first time a modalias is queried.
+    pub fn query<S: AsRef<OsStr>>(&self, modalias: S) -> Arc<Vec<OwnedEntry>> {
+        let modalias = modalias.as_ref();
+        let mut cache = self.cache.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(entries) = cache.get(modalias) {
+            return entries.clone();
+        }
+
+        let entries = Arc::new(self.hwdb.query(modalias).map(OwnedEntry::from).collect());
+        cache.insert(modalias.to_owned(), Arc::clone(&entries));
+        entries
+    }
+
+    /// Returns the underlying database.
+    pub fn hwdb(&self) -> &Hwdb {
+        &self.hwdb
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn round_trip_to_raw_pointers() {
+        let udev = Udev::new().unwrap();
+        let hwdb = Hwdb::with_context(udev.clone()).unwrap();
+
+        // Round-trip this to raw pointers and back again
+        let udev = udev.into_raw();
+        let ptr = AsRaw::into_raw(hwdb);
+
+        let hwdb = unsafe { Hwdb::from_raw_with_context(udev, ptr) };
+
+        // Everything should still work just the same after round-tripping
+        let _ = hwdb.query("usb:v1D6Bp0001").collect::<Vec<_>>();
+    }
+
+    #[test]
+    fn round_trip_to_raw_pointer() {
+        let hwdb = Hwdb::new().unwrap();
+
+        // Round-trip this to a raw pointer and back again, without the context
+        let ptr = AsRaw::into_raw(hwdb);
+
+        let hwdb = unsafe { Hwdb::from_raw(ptr) };
+
+        // Everything should still work just the same after round-tripping
+        let _ = hwdb.query("usb:v1D6Bp0001").collect::<Vec<_>>();
+    }
+
+    #[test]
+    fn as_raw_is_unambiguous() {
+        // Code handling other types often has both traits in scope.
+        #[allow(unused_imports)]
+        use crate::AsRawWithContext;
+
+        // Only checks that this compiles, so no hwdb is needed.
+        let _ = |hwdb: &Hwdb| hwdb.as_raw();
+    }
+
     #[test]
     fn test_query() {
         let hwdb = Hwdb::new().unwrap();
@@ -110,4 +233,27 @@ mod tests {
 
         assert_eq!(value, "1.1 root hub");
     }
+
+    #[test]
+    fn test_cached_query() {
+        let hwdb = CachedHwdb::new(Hwdb::new().unwrap());
+
+        let first = hwdb.query("usb:v1D6Bp0001");
+        let second = hwdb.query("usb:v1D6Bp0001");
+
+        assert!(first
+            .iter()
+            .any(|e| e.name() == "ID_MODEL_FROM_DATABASE" && e.value() == "1.1 root hub"));
+
+        // The second query is answered from the cache, without reading the database again.
+        assert!(Arc::ptr_eq(&first, &second));
+    }
+
+    #[test]
+    fn test_contains_property() {
+        let hwdb = Hwdb::new().unwrap();
+
+        assert!(hwdb.contains_property("usb:v1D6Bp0001", "ID_MODEL_FROM_DATABASE"));
+        assert!(!hwdb.contains_property("usb:v1D6Bp0001", "ID_NOT_A_PROPERTY"));
+    }
 }
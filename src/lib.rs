@@ -16,23 +16,45 @@ pub extern crate mio08 as mio;
 #[cfg(feature = "mio10")]
 pub extern crate mio10 as mio;
 
-pub use device::{Attributes, Device, DeviceType, Properties};
+pub use device::{
+    Ancestors, Attributes, DevLinks, Device, DeviceFactory, DeviceType, Properties, Tags,
+};
+pub use device_cache::DeviceCache;
 pub use enumerator::{Devices, Enumerator};
 #[cfg(feature = "hwdb")]
-pub use hwdb::Hwdb;
-pub use list::{Entry, List};
+pub use hwdb::{CachedHwdb, Hwdb};
+pub use list::{Entry, List, OwnedEntry};
+pub use match_expr::MatchExpr;
 pub use monitor::{
-    Builder as MonitorBuilder, Event, EventType, Socket as MonitorSocket,
-    SocketIter as MonitorSocketIter,
+    Builder as MonitorBuilder, Event, EventType, Filter as MonitorFilter, Monitor,
+    Socket as MonitorSocket, SocketIter as MonitorSocketIter, SocketStats as MonitorSocketStats,
 };
+#[cfg(target_os = "linux")]
+pub use monitor::{EventLoop, EventLoopIter, MultiSocket, MultiSocketIter, SocketId};
+pub use subsystem::{BlockDevice, FsInfo, InputCaps, NetDevice, Partition, PowerSupply, Subsystem};
 pub use udev::Udev;
+pub use util::is_transient;
 
 macro_rules! try_alloc {
-    ($exp:expr) => {{
-        let ptr = $exp;
+    (unsafe { ffi::$func:ident($($arg:expr),* $(,)*) }) => {{
+        // Clear `errno` first, so a stale value left behind by an earlier call isn't reported as
+        // the reason for this failure.
+        $crate::util::set_errno(0);
+
+        let ptr = unsafe { ffi::$func($($arg),*) };
 
         if ptr.is_null() {
-            return Err(std::io::Error::last_os_error());
+            let err = std::io::Error::last_os_error();
+
+            // Not every libudev constructor sets `errno` when it fails.
+            if err.raw_os_error() == Some(0) {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    concat!(stringify!($func), " returned null"),
+                ));
+            }
+
+            return Err(err);
         }
 
         ptr
@@ -99,6 +121,10 @@ pub trait FromRawWithContext<T: 'static> {
     /// behaviour might occur.  Do NOT attempt to free either pointer; `udev_unref` and the
     /// corresponding `*_unref` function for `ptr` will be called automatically when this type is
     /// dropped.
+    ///
+    /// In debug builds, passing a `udev` pointer other than the one `ptr` was created with panics,
+    /// for the types where libudev records the context (all but `Hwdb`). libudev doesn't expose
+    /// reference counts, so a context that is freed too early can't be detected.
     unsafe fn from_raw_with_context(udev: *mut ffi::udev, ptr: *mut T) -> Self;
 }
 
@@ -111,9 +137,9 @@ macro_rules! as_ffi {
 }
 
 macro_rules! as_ffi_with_context {
-    ($struct_:ident, $field:ident, $type_:ty, $ref:path) => {
+    ($struct_:ident, $field:ident, $type_:ty, $ref:path $(, $get_udev:path)*) => {
         as_raw_with_context!($struct_, $field, $type_, $ref);
-        from_raw_with_context!($struct_, $field, $type_);
+        from_raw_with_context!($struct_, $field, $type_ $(, $get_udev)*);
     };
 }
 
@@ -184,9 +210,19 @@ macro_rules! as_raw_with_context {
 }
 
 macro_rules! from_raw_with_context {
-    ($struct_:ident, $field:ident, $type_:ty) => {
+    ($struct_:ident, $field:ident, $type_:ty $(, $get_udev:path)*) => {
         impl $crate::FromRawWithContext<$type_> for $struct_ {
             unsafe fn from_raw_with_context(udev: *mut ffi::udev, t: *mut $type_) -> Self {
+                $(
+                    debug_assert!(
+                        $crate::util::context_matches(udev, $get_udev(t)),
+                        concat!(
+                            stringify!($struct_),
+                            " paired with a udev context it wasn't created with"
+                        )
+                    );
+                )*
+
                 Self {
                     udev: Udev::from_raw(udev),
                     $field: t,
@@ -197,10 +233,15 @@ macro_rules! from_raw_with_context {
 }
 
 mod device;
+mod device_cache;
 mod enumerator;
+#[cfg(target_os = "linux")]
+mod epoll;
 #[cfg(feature = "hwdb")]
 mod hwdb;
 mod list;
+mod match_expr;
 mod monitor;
+mod subsystem;
 mod udev;
 mod util;
@@ -1,8 +1,10 @@
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
+use std::iter::FusedIterator;
 use std::marker::PhantomData;
 
 use ffi;
 use util;
+use Udev;
 
 /// Rust wrapper for the `udev_list_entry` struct, which provides sequential
 /// access to an associative list of string names and values.
@@ -12,6 +14,8 @@ use util;
 /// some open handle to the `udev` hardware database.
 pub struct List<'a, T: 'a, E: 'a> {
     pub(crate) entry: *mut ffi::udev_list_entry,
+    /// The context to create the list's elements with, for lists of objects rather than entries.
+    pub(crate) udev: Option<Udev>,
     pub(crate) phantom: PhantomData<&'a (T, E)>,
 }
 
@@ -44,6 +48,21 @@ impl<'a, T> Iterator for EntryList<'a, T> {
     }
 }
 
+impl<'a, T> EntryList<'a, T> {
+    /// Returns the first remaining entry with the given value, advancing the list past it.
+    ///
+    /// Entries without a value match the empty string, consistent with `Entry::value()`. Returns
+    /// `None`, leaving the list exhausted, if no entry matches.
+    pub fn find_by_value<V: AsRef<OsStr>>(&mut self, value: V) -> Option<Entry<'a>> {
+        let value = value.as_ref();
+
+        self.find(|entry| entry.value() == value)
+    }
+}
+
+// Once the end of the list is reached, `entry` stays null.
+impl<'a, T> FusedIterator for EntryList<'a, T> {}
+
 /// Rust wrapper for each entry in `List`, each of which contains a name and a value.
 pub struct Entry<'a> {
     pub(crate) name: &'a OsStr,
@@ -61,3 +80,31 @@ impl<'a> Entry<'a> {
         self.value.unwrap_or_else(|| OsStr::new(""))
     }
 }
+
+/// An owned copy of an `Entry`, which does not borrow from the list it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OwnedEntry {
+    name: OsString,
+    value: Option<OsString>,
+}
+
+impl OwnedEntry {
+    /// Returns the entry name.
+    pub fn name(&self) -> &OsStr {
+        &self.name
+    }
+
+    /// Returns the entry value.
+    pub fn value(&self) -> &OsStr {
+        self.value.as_deref().unwrap_or_else(|| OsStr::new(""))
+    }
+}
+
+impl<'a> From<Entry<'a>> for OwnedEntry {
+    fn from(entry: Entry<'a>) -> Self {
+        Self {
+            name: entry.name.to_owned(),
+            value: entry.value.map(OsStr::to_owned),
+        }
+    }
+}
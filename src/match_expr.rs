@@ -0,0 +1,238 @@
+use std::ffi::{CString, OsStr, OsString};
+use std::iter;
+use std::os::unix::ffi::OsStrExt;
+
+use Device;
+
+/// A match expression in the style of a udev rule, evaluated by `Device::matches()`.
+///
+/// Each method adds a condition, and a device matches if all conditions hold. The conditions on
+/// the device itself correspond to the rule keys `SUBSYSTEM`, `DEVTYPE`, `KERNEL`, `DRIVER`,
+/// `ATTR`, `ENV` and `TAG`. The `ancestor_*` conditions correspond to `SUBSYSTEMS`, `KERNELS`,
+/// `DRIVERS`, `ATTRS` and `TAGS`: as in udev, they must all hold for the same device, which is
+/// either the device itself or one of its parents.
+///
+/// Values are shell-style glob patterns, matched with `fnmatch(3)`, and may list alternatives
+/// separated by `|`, e.g. `sd*|vd*`. A missing value is matched as the empty string, so `""`
+/// matches devices without the attribute or property.
+///
+/// ## Example
+///
+/// This is the equivalent of `SUBSYSTEM=="hidraw", ATTRS{idVendor}=="046d"`:
+///
+/// ```no_run
+/// # let device = udev::Device::from_syspath("/sys/class/hidraw/hidraw0").unwrap();
+/// let expr = udev::MatchExpr::new()
+///     .subsystem("hidraw")
+///     .ancestor_attribute("idVendor", "046d");
+///
+/// if device.matches(&expr) {
+///     println!("Found a Logitech device at {:?}", device.devnode());
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MatchExpr {
+    conditions: Vec<Condition>,
+    ancestor_conditions: Vec<Condition>,
+}
+
+/// A single condition of a `MatchExpr`, holding the glob pattern to match.
+#[derive(Clone, Debug)]
+enum Condition {
+    Subsystem(OsString),
+    Devtype(OsString),
+    Sysname(OsString),
+    Driver(OsString),
+    Attribute(OsString, OsString),
+    Property(OsString, OsString),
+    Tag(OsString),
+}
+
+impl MatchExpr {
+    /// Creates an empty match expression, which matches every device.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Matches devices whose subsystem matches the pattern, like `SUBSYSTEM`.
+    pub fn subsystem<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.conditions
+            .push(Condition::Subsystem(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices whose device type matches the pattern, like `DEVTYPE`.
+    pub fn devtype<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.conditions
+            .push(Condition::Devtype(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices whose kernel device name matches the pattern, like `KERNEL`.
+    pub fn sysname<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.conditions
+            .push(Condition::Sysname(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices whose driver matches the pattern, like `DRIVER`.
+    pub fn driver<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.conditions
+            .push(Condition::Driver(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices whose attribute value matches the pattern, like `ATTR`.
+    pub fn attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(mut self, attribute: T, pattern: U) -> Self {
+        self.conditions.push(Condition::Attribute(
+            attribute.as_ref().to_owned(),
+            pattern.as_ref().to_owned(),
+        ));
+        self
+    }
+
+    /// Matches devices whose property value matches the pattern, like `ENV`.
+    pub fn property<T: AsRef<OsStr>, U: AsRef<OsStr>>(mut self, property: T, pattern: U) -> Self {
+        self.conditions.push(Condition::Property(
+            property.as_ref().to_owned(),
+            pattern.as_ref().to_owned(),
+        ));
+        self
+    }
+
+    /// Matches devices that have a tag matching the pattern, like `TAG`.
+    pub fn tag<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.conditions
+            .push(Condition::Tag(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices with an ancestor whose subsystem matches the pattern, like `SUBSYSTEMS`.
+    pub fn ancestor_subsystem<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.ancestor_conditions
+            .push(Condition::Subsystem(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices with an ancestor whose kernel device name matches the pattern, like
+    /// `KERNELS`.
+    pub fn ancestor_sysname<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.ancestor_conditions
+            .push(Condition::Sysname(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices with an ancestor whose driver matches the pattern, like `DRIVERS`.
+    pub fn ancestor_driver<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.ancestor_conditions
+            .push(Condition::Driver(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Matches devices with an ancestor whose attribute value matches the pattern, like `ATTRS`.
+    pub fn ancestor_attribute<T: AsRef<OsStr>, U: AsRef<OsStr>>(
+        mut self,
+        attribute: T,
+        pattern: U,
+    ) -> Self {
+        self.ancestor_conditions.push(Condition::Attribute(
+            attribute.as_ref().to_owned(),
+            pattern.as_ref().to_owned(),
+        ));
+        self
+    }
+
+    /// Matches devices with an ancestor that has a tag matching the pattern, like `TAGS`.
+    pub fn ancestor_tag<T: AsRef<OsStr>>(mut self, pattern: T) -> Self {
+        self.ancestor_conditions
+            .push(Condition::Tag(pattern.as_ref().to_owned()));
+        self
+    }
+
+    /// Evaluates the expression against `device`.
+    pub(crate) fn matches(&self, device: &Device) -> bool {
+        if !self.conditions.iter().all(|cond| cond.matches(device)) {
+            return false;
+        }
+
+        if self.ancestor_conditions.is_empty() {
+            return true;
+        }
+
+        iter::once(device.clone())
+            .chain(device.ancestors())
+            .any(|device| {
+                self.ancestor_conditions
+                    .iter()
+                    .all(|cond| cond.matches(&device))
+            })
+    }
+}
+
+impl Condition {
+    fn matches(&self, device: &Device) -> bool {
+        match *self {
+            Condition::Subsystem(ref pattern) => glob(pattern, device.subsystem()),
+            Condition::Devtype(ref pattern) => glob(pattern, device.devtype()),
+            Condition::Sysname(ref pattern) => glob(pattern, Some(device.sysname())),
+            Condition::Driver(ref pattern) => glob(pattern, device.driver()),
+            Condition::Attribute(ref attribute, ref pattern) => {
+                glob(pattern, device.attribute_value(attribute))
+            }
+            Condition::Property(ref property, ref pattern) => {
+                glob(pattern, device.property_value(property))
+            }
+            Condition::Tag(ref pattern) => device.tags().any(|tag| glob(pattern, Some(tag))),
+        }
+    }
+}
+
+/// Matches `value` against a glob pattern with `|`-separated alternatives, treating a missing
+/// value as the empty string.
+fn glob(pattern: &OsStr, value: Option<&OsStr>) -> bool {
+    let value = match CString::new(value.map_or(&[][..], OsStr::as_bytes)) {
+        Ok(value) => value,
+        Err(_) => return false,
+    };
+
+    pattern.as_bytes().split(|&b| b == b'|').any(|alternative| {
+        CString::new(alternative).is_ok_and(|alternative| unsafe {
+            libc::fnmatch(alternative.as_ptr(), value.as_ptr(), 0) == 0
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn glob_str(pattern: &str, value: Option<&str>) -> bool {
+        glob(OsStr::new(pattern), value.map(OsStr::new))
+    }
+
+    #[test]
+    fn glob_matches_any_alternative() {
+        assert!(glob_str("sd*|nvme*", Some("sda")));
+        assert!(glob_str("sd*|nvme*", Some("nvme0n1")));
+        assert!(!glob_str("sd*|nvme*", Some("mmcblk0")));
+        assert!(glob_str("tty[0-9]", Some("tty1")));
+    }
+
+    #[test]
+    fn glob_treats_missing_value_as_empty() {
+        assert!(glob_str("", None));
+        assert!(glob_str("", Some("")));
+        assert!(glob_str("*", None));
+        assert!(glob_str("usb|", None));
+        assert!(!glob_str("?*", None));
+        assert!(!glob_str("", Some("sda")));
+    }
+
+    #[test]
+    fn glob_skips_alternatives_with_nul_bytes() {
+        assert!(!glob_str("sd\0a", Some("sd")));
+        assert!(!glob_str("sd\0a", Some("sd\0a")));
+        assert!(glob_str("sd\0a|sd*", Some("sdb")));
+        assert!(!glob_str("*", Some("sd\0a")));
+    }
+}
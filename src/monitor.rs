@@ -1,10 +1,19 @@
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::collections::VecDeque;
 use std::fmt;
 use std::ptr;
 
-use std::ffi::OsStr;
+use std::ffi::{OsStr, OsString};
 use std::io::Result;
 use std::ops::Deref;
 use std::os::unix::io::{AsRawFd, RawFd};
+#[cfg(target_os = "linux")]
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::task::{self, Context};
+use std::thread;
+use std::time::{Duration, Instant};
 
 use io_lifetimes::{AsFd, BorrowedFd};
 #[cfg(feature = "mio06")]
@@ -12,19 +21,26 @@ use mio::{event::Evented, unix::EventedFd, Poll, PollOpt, Ready, Token};
 #[cfg(any(feature = "mio07", feature = "mio08", feature = "mio10"))]
 use mio::{event::Source, unix::SourceFd, Interest, Registry, Token};
 
+#[cfg(target_os = "linux")]
+use epoll::Epoll;
 use Udev;
 use {ffi, util};
 
-use {AsRaw, AsRawWithContext, Device, FromRaw};
+use {AsRaw, AsRawWithContext, Device, Enumerator, FromRaw, FromRawWithContext};
 
 /// Monitors for device events.
 ///
 /// A monitor communicates with the kernel over a socket. Filtering events is performed efficiently
 /// in the kernel, and only events that match the filters are received by the socket. Filters must
 /// be set up before listening for events.
+///
+/// The `match_*` methods consume the builder and return it with the filter added, so the returned
+/// builder must be used for the filter to take effect.
+#[must_use = "filters are only applied to the returned builder"]
 pub struct Builder {
     udev: Udev,
     monitor: *mut ffi::udev_monitor,
+    filters: Vec<Filter>,
 }
 
 impl Clone for Builder {
@@ -32,6 +48,7 @@ impl Clone for Builder {
         Self {
             udev: self.udev.clone(),
             monitor: unsafe { ffi::udev_monitor_ref(self.monitor) },
+            filters: self.filters.clone(),
         }
     }
 }
@@ -49,7 +66,43 @@ unsafe impl Send for Builder {}
 #[cfg(feature = "sync")]
 unsafe impl Sync for Builder {}
 
-as_ffi_with_context!(Builder, monitor, ffi::udev_monitor, ffi::udev_monitor_ref);
+as_raw_with_context!(Builder, monitor, ffi::udev_monitor, ffi::udev_monitor_ref);
+
+impl FromRawWithContext<ffi::udev_monitor> for Builder {
+    /// Creates a `Builder` from raw pointers.
+    ///
+    /// Filters that were added to the monitor before it was converted are not known to the
+    /// returned builder, so they are not included in `Monitor::filters()`.
+    unsafe fn from_raw_with_context(udev: *mut ffi::udev, monitor: *mut ffi::udev_monitor) -> Self {
+        debug_assert!(
+            util::context_matches(udev, ffi::udev_monitor_get_udev(monitor)),
+            "Builder paired with a udev context it wasn't created with"
+        );
+
+        Self {
+            udev: Udev::from_raw(udev),
+            monitor,
+            filters: Vec::new(),
+        }
+    }
+}
+
+/// A filter added to a monitor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    /// Matches devices with the given subsystem, see `Builder::match_subsystem()`.
+    Subsystem(OsString),
+
+    /// Matches devices with the given subsystem and device type, see
+    /// `Builder::match_subsystem_devtype()`.
+    SubsystemDevtype(OsString, OsString),
+
+    /// Matches devices with the given tag, see `Builder::match_tag()`.
+    Tag(OsString),
+
+    /// Drops devices with the given subsystem, see `Builder::nomatch_subsystem()`.
+    NomatchSubsystem(OsString),
+}
 
 /// The event source to monitor.
 pub(crate) enum EventSource {
@@ -60,15 +113,13 @@ pub(crate) enum EventSource {
 impl Builder {
     /// Creates a new udev event `Monitor`.
     pub fn new() -> Result<Self> {
-        // Create a new Udev context for this monitor
-        // It would be more efficient to allow callers to create just one context and use multiple
-        // monitors, however that would be an API-breaking change.
-        Self::with_udev(Udev::new()?)
+        // Reuse this thread's Udev context
+        Self::with_udev(Udev::cached()?)
     }
 
     /// Creates a new kernel event `Monitor`.
     pub fn new_kernel() -> Result<Self> {
-        Self::with_kernel(Udev::new()?)
+        Self::with_kernel(Udev::cached()?)
     }
 
     /// Creates a new `Monitor` using an existing `Udev` instance
@@ -90,11 +141,32 @@ impl Builder {
 
         let ptr = try_alloc!(unsafe { ffi::udev_monitor_new_from_netlink(udev.as_raw(), name) });
 
-        Ok(Self { udev, monitor: ptr })
+        Ok(Self {
+            udev,
+            monitor: ptr,
+            filters: Vec::new(),
+        })
+    }
+
+    /// Creates a `Monitor` for the netlink event source with the given name, using an existing
+    /// `Udev` instance.
+    ///
+    /// The `name` parameter must be either `"udev"`, for events sent out after udev has processed
+    /// the device, or `"kernel"`, for the raw events sent out by the kernel. Any other name is
+    /// rejected with `EINVAL`.
+    pub fn with_source_name(udev: Udev, name: &str) -> Result<Self> {
+        let source = match name {
+            "udev" => EventSource::Udev,
+            "kernel" => EventSource::Kernel,
+            _ => return Err(std::io::Error::from_raw_os_error(libc::EINVAL)),
+        };
+
+        Self::with_source(udev, source)
     }
 
     /// Adds a filter that matches events for devices with the given subsystem.
-    pub fn match_subsystem<T: AsRef<OsStr>>(self, subsystem: T) -> Result<Self> {
+    pub fn match_subsystem<T: AsRef<OsStr>>(mut self, subsystem: T) -> Result<Self> {
+        let filter = Filter::Subsystem(subsystem.as_ref().to_owned());
         let subsystem = util::os_str_to_cstring(subsystem)?;
 
         util::errno_to_result(unsafe {
@@ -103,16 +175,20 @@ impl Builder {
                 subsystem.as_ptr(),
                 ptr::null(),
             )
-        })
-        .and(Ok(self))
+        })?;
+
+        self.filters.push(filter);
+        Ok(self)
     }
 
     /// Adds a filter that matches events for devices with the given subsystem and device type.
     pub fn match_subsystem_devtype<T: AsRef<OsStr>, U: AsRef<OsStr>>(
-        self,
+        mut self,
         subsystem: T,
         devtype: U,
     ) -> Result<Self> {
+        let filter =
+            Filter::SubsystemDevtype(subsystem.as_ref().to_owned(), devtype.as_ref().to_owned());
         let subsystem = util::os_str_to_cstring(subsystem)?;
         let devtype = util::os_str_to_cstring(devtype)?;
 
@@ -122,33 +198,71 @@ impl Builder {
                 subsystem.as_ptr(),
                 devtype.as_ptr(),
             )
-        })
-        .and(Ok(self))
+        })?;
+
+        self.filters.push(filter);
+        Ok(self)
     }
 
     /// Adds a filter that matches events for devices with the given tag.
-    pub fn match_tag<T: AsRef<OsStr>>(self, tag: T) -> Result<Self> {
+    pub fn match_tag<T: AsRef<OsStr>>(mut self, tag: T) -> Result<Self> {
+        let filter = Filter::Tag(tag.as_ref().to_owned());
         let tag = util::os_str_to_cstring(tag)?;
 
         util::errno_to_result(unsafe {
             ffi::udev_monitor_filter_add_match_tag(self.monitor, tag.as_ptr())
-        })
-        .and(Ok(self))
+        })?;
+
+        self.filters.push(filter);
+        Ok(self)
+    }
+
+    /// Adds a filter that drops events for devices with the given subsystem.
+    ///
+    /// The kernel has no filter for excluding subsystems, so unlike the `match_*` filters, this
+    /// one is applied in userspace: every event that passes the other filters is still received
+    /// and decoded, and then dropped by `Socket::iter()` and the methods built on it. Prefer
+    /// `match_subsystem()` for the subsystems of interest where possible.
+    pub fn nomatch_subsystem<T: AsRef<OsStr>>(mut self, subsystem: T) -> Result<Self> {
+        self.filters
+            .push(Filter::NomatchSubsystem(subsystem.as_ref().to_owned()));
+        Ok(self)
     }
 
     /// Removes all filters currently set on the monitor.
-    pub fn clear_filters(self) -> Result<Self> {
-        util::errno_to_result(unsafe { ffi::udev_monitor_filter_remove(self.monitor) })
-            .and(Ok(self))
+    pub fn clear_filters(mut self) -> Result<Self> {
+        util::errno_to_result(unsafe { ffi::udev_monitor_filter_remove(self.monitor) })?;
+
+        self.filters.clear();
+        Ok(self)
+    }
+
+    /// Returns the filters added to the builder, in the order in which they were added.
+    ///
+    /// libudev doesn't expose the filters of a monitor, so only filters added through the builder
+    /// are included.
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    /// Returns the number of filters added to the builder.
+    pub fn filter_count(&self) -> usize {
+        self.filters.len()
+    }
+
+    /// Finishes setting up filters, returning a `Monitor` that is not receiving events yet.
+    ///
+    /// Call `Monitor::listen()` to start receiving events.
+    pub fn build(self) -> Monitor {
+        Monitor { inner: self }
     }
 
     /// Listens for events matching the current filters.
     ///
-    /// This method consumes the `Monitor`.
+    /// This method consumes the `Monitor`. It is a shorthand for `build()` followed by
+    /// `Monitor::listen()`.
     pub fn listen(self) -> Result<Socket> {
-        util::errno_to_result(unsafe { ffi::udev_monitor_enable_receiving(self.monitor) })?;
-
-        Ok(Socket { inner: self })
+        self.build().listen()
     }
 }
 
@@ -168,6 +282,42 @@ impl AsFd for Builder {
     }
 }
 
+/// A monitor whose filters are set up, but which is not receiving events yet.
+///
+/// A `Monitor` is created by `Builder::build()`, and starts receiving events once `listen()` is
+/// called.
+#[derive(Clone)]
+pub struct Monitor {
+    inner: Builder,
+}
+
+impl Monitor {
+    /// Returns the filters set up on the monitor, in the order in which they were added.
+    pub fn filters(&self) -> &[Filter] {
+        &self.inner.filters
+    }
+
+    /// Listens for events matching the monitor's filters.
+    ///
+    /// This method consumes the `Monitor`.
+    pub fn listen(self) -> Result<Socket> {
+        util::errno_to_result(unsafe { ffi::udev_monitor_enable_receiving(self.inner.monitor) })?;
+
+        // libudev asks for the sender's credentials already, but `read_raw()` relies on them.
+        #[cfg(target_os = "linux")]
+        pass_credentials(unsafe { ffi::udev_monitor_get_fd(self.inner.monitor) })?;
+
+        Ok(Socket {
+            inner: self.inner,
+            events_received: util::SharedU64::new(0),
+            bytes_received: util::SharedU64::new(0),
+            overflows: util::SharedU64::new(0),
+            min_seqnum: util::SharedU64::new(0),
+            paused: AtomicBool::new(false),
+        })
+    }
+}
+
 /// An active monitor that can receive events.
 ///
 /// The events received by a `Socket` match the filters set up by the `Monitor` that created
@@ -178,6 +328,28 @@ impl AsFd for Builder {
 /// wait for new events.
 pub struct Socket {
     inner: Builder,
+    events_received: util::SharedU64,
+    bytes_received: util::SharedU64,
+    overflows: util::SharedU64,
+    min_seqnum: util::SharedU64,
+    paused: AtomicBool,
+}
+
+/// Statistics about the events received by a `Socket`, as returned by `Socket::stats()`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SocketStats {
+    /// The number of events received through `iter()` and the methods built on it.
+    pub events_received: u64,
+
+    /// The number of bytes received through `read_raw()`.
+    ///
+    /// libudev doesn't report the size of the messages it receives, so events received through
+    /// `iter()` are not included.
+    pub bytes_received: u64,
+
+    /// The number of times the socket's receive buffer overflowed, each of which lost one or more
+    /// events.
+    pub overflows: u64,
 }
 
 impl Socket {
@@ -185,6 +357,410 @@ impl Socket {
     pub fn iter(&self) -> SocketIter {
         SocketIter::new(&self)
     }
+
+    /// Create an iterator of socket event messages, yielding only events of the given types.
+    ///
+    /// Events of other types are received and dropped. Unlike the `match_*` filters of the
+    /// `Builder`, which are applied in the kernel, the kernel has no filter for actions, so this
+    /// filtering happens in userspace.
+    pub fn iter_actions<'a>(
+        &'a self,
+        actions: &'a [EventType],
+    ) -> impl Iterator<Item = Event> + 'a {
+        self.iter()
+            .filter(move |event| actions.contains(&event.event_type()))
+    }
+
+    /// Receives all pending events, without blocking.
+    ///
+    /// Unlike `iter()`, which stops early when a message is dropped by a filter, this keeps
+    /// reading until no event is pending, so the socket is fully drained. That is what
+    /// edge-triggered readiness, as with mio, requires: otherwise, events left behind aren't
+    /// reported again until another one arrives. A receive buffer overflow doesn't stop the
+    /// draining either; it's counted in `stats()`. Returns an empty `Vec` if the socket is paused.
+    pub fn drain(&self) -> Vec<Event> {
+        let mut events = Vec::new();
+
+        loop {
+            match self.receive() {
+                Ok(event) => events.push(event),
+                // The message was dropped by a filter, or the read was interrupted; keep going,
+                // so pending events aren't left behind.
+                Err(ref e) if e.raw_os_error() == Some(0) => continue,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                // The receive buffer overflowed. The overflow is counted in `stats()` and the
+                // error is cleared, so keep reading the events that did fit.
+                Err(ref e) if e.raw_os_error() == Some(libc::ENOBUFS) => continue,
+                Err(_) => break,
+            }
+        }
+
+        events
+    }
+
+    /// Receives all pending events, grouped by the subsystem of their device.
+    ///
+    /// Like `drain()`, this returns once no event is pending, so events are never left behind
+    /// for a later call. Within each group, events are kept in the order in which they arrived,
+    /// but the order across subsystems is lost, so this is meant for consumers that handle each
+    /// subsystem independently, e.g. to process the important ones first. Events for devices
+    /// without a subsystem are grouped under an empty name.
+    pub fn iter_grouped(&self) -> HashMap<OsString, Vec<Event>> {
+        let mut groups: HashMap<OsString, Vec<Event>> = HashMap::new();
+
+        for event in self.drain() {
+            let subsystem = event.subsystem().unwrap_or_else(|| OsStr::new(""));
+
+            groups.entry(subsystem.to_owned()).or_default().push(event);
+        }
+
+        groups
+    }
+
+    /// Returns statistics about the events received since the socket was created.
+    ///
+    /// libudev doesn't keep track of these, so only events received through this crate are
+    /// counted.
+    pub fn stats(&self) -> SocketStats {
+        SocketStats {
+            events_received: self.events_received.load(),
+            bytes_received: self.bytes_received.load(),
+            overflows: self.overflows.load(),
+        }
+    }
+
+    /// Skips events with a sequence number lower than `seqnum`.
+    ///
+    /// This is useful for daemons that persist the sequence number of the last event they
+    /// processed, so events that were buffered before a restart aren't processed twice. libudev
+    /// has no such filter, so the events are received and dropped in userspace by `iter()` and
+    /// the methods built on it. Skipped events are not counted in `stats()`.
+    ///
+    /// Pass `0` to stop skipping events.
+    pub fn set_min_seqnum(&self, seqnum: u64) {
+        self.min_seqnum.store(seqnum);
+    }
+
+    /// Stops delivering events until `resume()` is called.
+    ///
+    /// While paused, `iter()` returns no events and `poll_recv()` returns `Poll::Pending`, as
+    /// does every other method that receives events, without reading from the socket. Events
+    /// keep arriving and are buffered by the kernel, so nothing is lost unless the socket's
+    /// receive buffer fills up. After a long pause, that can happen easily; events are then
+    /// dropped, which is counted in `stats()`, and it's best to `resync()`. `read_raw()` is not
+    /// affected.
+    ///
+    /// The socket's file descriptor stays readable while events are buffered. A level-triggered
+    /// poll loop should stop polling the socket while it's paused, or it will spin. With
+    /// edge-triggered readiness, as with mio, no new readiness is reported for the events that
+    /// arrived while paused, so drain the socket with `iter()` right after resuming.
+    ///
+    /// The waiting done by this crate, as in `iter_coalesced()`, `MultiSocket::wait()` and
+    /// `EventLoop`, skips paused sockets, and notices within 100 ms that a socket was resumed.
+    pub fn pause(&self) {
+        self.paused.store(true, Ordering::Relaxed);
+    }
+
+    /// Resumes delivering events after `pause()`, starting with the events buffered meanwhile.
+    pub fn resume(&self) {
+        self.paused.store(false, Ordering::Relaxed);
+    }
+
+    /// Returns whether event delivery is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Attempts to receive an event without blocking, for use in a `Future` implementation.
+    ///
+    /// Returns `Poll::Pending` if no event is pending on the socket. Unlike most `poll_*` methods,
+    /// this does not arrange for the task to be woken up: the caller must wait for the socket's
+    /// file descriptor to become readable, e.g. by registering it with its executor's reactor, and
+    /// wake the task itself before polling again.
+    pub fn poll_recv(&self, _cx: &mut Context<'_>) -> task::Poll<Result<Event>> {
+        loop {
+            match self.receive() {
+                Ok(event) => return task::Poll::Ready(Ok(event)),
+                Err(ref e) if e.raw_os_error() == Some(libc::EAGAIN) => return task::Poll::Pending,
+                // The message was dropped by a filter, or the read was interrupted; try the next
+                // one.
+                Err(ref e) if e.raw_os_error() == Some(0) => continue,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return task::Poll::Ready(Err(e)),
+            }
+        }
+    }
+
+    /// Receives the next event from the socket, keeping the statistics up to date.
+    ///
+    /// Events below the minimum sequence number or excluded by a `nomatch_subsystem()` filter are
+    /// skipped. While paused, fails with `EAGAIN` as if no event was pending. The returned error
+    /// carries `errno` as set by libudev, which is `0` if it didn't set it.
+    fn receive(&self) -> Result<Event> {
+        if self.is_paused() {
+            return Err(std::io::Error::from_raw_os_error(libc::EAGAIN));
+        }
+
+        loop {
+            // Clear `errno` first, so a stale `ENOBUFS` isn't counted as an overflow.
+            util::set_errno(0);
+
+            let ptr = unsafe { ffi::udev_monitor_receive_device(self.inner.monitor) };
+
+            if ptr.is_null() {
+                let err = std::io::Error::last_os_error();
+
+                if err.raw_os_error() == Some(libc::ENOBUFS) {
+                    self.overflows.add(1);
+                }
+
+                return Err(err);
+            }
+
+            let device = Device::from_raw(self.inner.udev.clone(), ptr);
+            let event = Event { device };
+
+            if event.sequence_number() < self.min_seqnum.load() {
+                continue;
+            }
+
+            if self.excludes(&event) {
+                continue;
+            }
+
+            self.events_received.add(1);
+
+            return Ok(event);
+        }
+    }
+
+    /// Returns whether the device is excluded by a `nomatch_subsystem()` filter.
+    fn excludes(&self, device: &Device) -> bool {
+        self.inner.filters.iter().any(|filter| match *filter {
+            Filter::NomatchSubsystem(ref subsystem) => device.subsystem() == Some(subsystem),
+            _ => false,
+        })
+    }
+
+    /// Reads one raw netlink message from the monitor's socket into `buf`, returning the number of
+    /// bytes read.
+    ///
+    /// Messages from the `kernel` source are a header of the form `ACTION@DEVPATH` followed by
+    /// NUL-separated `KEY=VALUE` pairs. Messages from the `udev` source start with a binary
+    /// `libudev` header instead.
+    ///
+    /// The returned length is that of the whole message, even if `buf` is too small for it. In
+    /// that case, the message is truncated to `buf.len()` bytes and the remainder is discarded.
+    ///
+    /// As libudev does, messages that weren't sent by root, and messages on the `kernel` source
+    /// that don't come from the kernel itself, are dropped. Other than that, the message is
+    /// returned as is: the socket's `nomatch_subsystem()` filters and `set_min_seqnum()` are not
+    /// applied, nor, for the `kernel` source, the `Builder`'s other filters.
+    ///
+    /// The message is consumed from the socket, so it will not also be returned by `iter()`. As
+    /// the socket is nonblocking, an error of kind `WouldBlock` is returned when no message is
+    /// pending.
+    ///
+    /// Only available on Linux, where the socket is a netlink socket.
+    #[cfg(target_os = "linux")]
+    pub fn read_raw(&self, buf: &mut [u8]) -> Result<usize> {
+        loop {
+            let (len, trusted) = match recv_message(self.as_raw_fd(), buf) {
+                Ok(message) => message,
+                Err(err) => {
+                    if err.raw_os_error() == Some(libc::ENOBUFS) {
+                        self.overflows.add(1);
+                    }
+
+                    return Err(err);
+                }
+            };
+
+            if !trusted {
+                continue;
+            }
+
+            self.bytes_received.add(len as u64);
+
+            return Ok(len);
+        }
+    }
+
+    /// Receives events for the duration of `window`, yielding only the most recent event for each
+    /// device.
+    ///
+    /// This blocks until `window` has elapsed, which makes it useful for debouncing devices that
+    /// emit a burst of `change` events. Events are keyed by syspath and yielded in the order in
+    /// which each device's latest event arrived. Receiving stops early if waiting on the socket
+    /// fails.
+    pub fn iter_coalesced(&self, window: Duration) -> impl Iterator<Item = Event> {
+        let deadline = Instant::now() + window;
+        let mut events: Vec<Event> = Vec::new();
+
+        loop {
+            for event in self.iter() {
+                events.retain(|e| e.syspath() != event.syspath());
+                events.push(event);
+            }
+
+            let now = Instant::now();
+
+            if now >= deadline {
+                break;
+            }
+
+            match self.wait(deadline - now) {
+                Ok(_) => continue,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                // Some events were lost, but the error is cleared, so keep receiving.
+                Err(ref e) if e.raw_os_error() == Some(libc::ENOBUFS) => continue,
+                Err(_) => break,
+            }
+        }
+
+        events.into_iter()
+    }
+
+    /// Returns and clears the pending error on the socket, if any.
+    ///
+    /// When the socket's receive buffer overflows, the kernel flags the socket with `ENOBUFS`,
+    /// which makes it report error readiness, e.g. `EPOLLERR` or mio's `Event::is_error()`. Until
+    /// the error is cleared, either by this method or by the next receive, the socket stays ready,
+    /// so an event loop should call this when it sees error readiness rather than only waiting for
+    /// readability. Events were lost, so it's best to re-enumerate the devices of interest.
+    pub fn take_error(&self) -> Result<Option<std::io::Error>> {
+        let mut error: libc::c_int = 0;
+        let mut len = std::mem::size_of::<libc::c_int>() as libc::socklen_t;
+
+        let ret = unsafe {
+            libc::getsockopt(
+                self.as_raw_fd(),
+                libc::SOL_SOCKET,
+                libc::SO_ERROR,
+                &mut error as *mut libc::c_int as *mut libc::c_void,
+                &mut len,
+            )
+        };
+
+        if ret < 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+
+        if error == 0 {
+            return Ok(None);
+        }
+
+        if error == libc::ENOBUFS {
+            self.overflows.add(1);
+        }
+
+        Ok(Some(std::io::Error::from_raw_os_error(error)))
+    }
+
+    /// Enumerates the devices currently present that match the socket's filters, calling `handle`
+    /// for each of them.
+    ///
+    /// This is the way to recover after the socket's receive buffer overflowed and events were
+    /// lost: drain the socket, call `resync()` and rebuild the state kept about the devices from
+    /// the devices passed to `handle`. Events that arrive during the scan are received as usual
+    /// afterwards, so they may repeat what the scan already reported.
+    ///
+    /// Only the filters known to the `Builder` are applied, see `Monitor::filters()`. The
+    /// enumerator has no device type filter, and it requires devices to carry all of the tags
+    /// matched, whereas the monitor lets devices with any of them through, so device type and tag
+    /// filters are applied in userspace.
+    pub fn resync<F: FnMut(Device)>(&self, mut handle: F) -> Result<()> {
+        let filters = &self.inner.filters;
+        let mut enumerator = Enumerator::with_udev(self.inner.udev.clone())?;
+
+        for filter in filters {
+            match *filter {
+                Filter::Subsystem(ref subsystem) | Filter::SubsystemDevtype(ref subsystem, _) => {
+                    enumerator.match_subsystem(subsystem)?
+                }
+                Filter::Tag(_) => {}
+                Filter::NomatchSubsystem(ref subsystem) => {
+                    enumerator.nomatch_subsystem(subsystem)?
+                }
+            }
+        }
+
+        // As in the kernel, a device must match one of the subsystem filters, if there are any.
+        let matches_subsystem = |device: &Device| {
+            let mut subsystem_filters = filters
+                .iter()
+                .filter(|filter| {
+                    matches!(
+                        filter,
+                        Filter::Subsystem(_) | Filter::SubsystemDevtype(_, _)
+                    )
+                })
+                .peekable();
+
+            subsystem_filters.peek().is_none()
+                || subsystem_filters.any(|filter| match *filter {
+                    Filter::Subsystem(ref subsystem) => device.subsystem() == Some(subsystem),
+                    Filter::SubsystemDevtype(ref subsystem, ref devtype) => {
+                        device.subsystem() == Some(subsystem) && device.devtype() == Some(devtype)
+                    }
+                    Filter::Tag(_) | Filter::NomatchSubsystem(_) => false,
+                })
+        };
+
+        // Likewise, a device must carry one of the tags matched, if any.
+        let matches_tag = |device: &Device| {
+            let mut tag_filters = filters
+                .iter()
+                .filter_map(|filter| match *filter {
+                    Filter::Tag(ref tag) => Some(tag),
+                    _ => None,
+                })
+                .peekable();
+
+            tag_filters.peek().is_none() || tag_filters.any(|tag| device.has_tag(tag))
+        };
+
+        for device in enumerator.scan_devices()? {
+            if matches_subsystem(&device) && matches_tag(&device) {
+                handle(device);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Waits up to `timeout` for the socket to become readable, returning whether it did.
+    ///
+    /// If the socket reports error readiness, the pending error is cleared and returned, so that
+    /// callers don't spin on a socket that stays ready.
+    ///
+    /// While the socket is paused, this sleeps instead, as the socket may stay readable, and
+    /// returns `false` after `timeout` or `PAUSE_POLL_INTERVAL`, whichever is shorter.
+    fn wait(&self, timeout: Duration) -> Result<bool> {
+        if self.is_paused() {
+            thread::sleep(timeout.min(PAUSE_POLL_INTERVAL));
+            return Ok(false);
+        }
+
+        let mut fd = libc::pollfd {
+            fd: self.as_raw_fd(),
+            events: libc::POLLIN,
+            revents: 0,
+        };
+
+        // Round up, so we don't wake up just before the timeout and spin.
+        let millis = timeout.as_micros().div_ceil(1000);
+        let millis = millis.min(libc::c_int::MAX as u128) as libc::c_int;
+
+        match unsafe { libc::poll(&mut fd, 1, millis) } {
+            n if n < 0 => Err(std::io::Error::last_os_error()),
+            _ if fd.revents & libc::POLLERR != 0 => match self.take_error()? {
+                Some(err) => Err(err),
+                None => Ok(true),
+            },
+            n => Ok(n > 0),
+        }
+    }
 }
 
 impl AsRaw<ffi::udev_monitor> for Socket {
@@ -214,6 +790,9 @@ impl AsFd for Socket {
 }
 
 /// Iterator of socket events
+///
+/// This iterator is not fused: after returning `None` because no event is pending, it returns
+/// events again once new ones arrive on the socket.
 pub struct SocketIter<'a> {
     socket: &'a Socket,
 }
@@ -229,14 +808,451 @@ impl<'a> Iterator for SocketIter<'a> {
     type Item = Event;
 
     fn next(&mut self) -> Option<Event> {
-        let ptr = unsafe { ffi::udev_monitor_receive_device(self.socket.inner.monitor) };
+        self.socket.receive().ok()
+    }
+}
 
-        if ptr.is_null() {
-            None
-        } else {
-            let device = Device::from_raw(self.socket.inner.udev.clone(), ptr);
-            Some(Event { device })
+/// Several monitor sockets joined together, so events from all of them can be received through a
+/// single file descriptor.
+///
+/// This is useful to receive events from both the `udev` and `kernel` sources, or from monitors
+/// with different filters. The file descriptor returned by the `AsRawFd` trait is an epoll file
+/// descriptor that is readable whenever any of the sockets is.
+///
+/// Only available on Linux, as it is built on epoll.
+#[cfg(target_os = "linux")]
+pub struct MultiSocket {
+    sockets: Vec<Socket>,
+    epoll: Epoll,
+    // Whether each socket is registered with `epoll`, which paused sockets are not.
+    registered: Vec<AtomicBool>,
+    // The socket `poll_recv()` tries first, so the sockets take turns across calls.
+    next: AtomicUsize,
+}
+
+#[cfg(target_os = "linux")]
+impl MultiSocket {
+    /// Joins the given sockets.
+    pub fn new(sockets: Vec<Socket>) -> Result<Self> {
+        let epoll = Epoll::new()?;
+
+        for (index, socket) in sockets.iter().enumerate() {
+            epoll.add(socket.as_raw_fd(), index as u64)?;
         }
+
+        Ok(Self {
+            registered: sockets.iter().map(|_| AtomicBool::new(true)).collect(),
+            sockets,
+            epoll,
+            next: AtomicUsize::new(0),
+        })
+    }
+
+    /// Returns the joined sockets.
+    pub fn sockets(&self) -> &[Socket] {
+        &self.sockets
+    }
+
+    /// Create an iterator of the events pending on any of the sockets.
+    ///
+    /// The sockets take turns, so a busy socket can't starve the others. Like `SocketIter`, the
+    /// iterator returns `None` once no socket has an event pending, and is not fused.
+    ///
+    /// Paused sockets are skipped, and are unregistered from the epoll file descriptor until they
+    /// are resumed, so that it doesn't stay readable for their pending events.
+    pub fn iter(&self) -> MultiSocketIter<'_> {
+        // Failing to update the registrations only costs spurious wakeups.
+        let _ = register_unpaused(&self.epoll, &self.sockets, &self.registered);
+
+        MultiSocketIter {
+            multi: self,
+            next: 0,
+            idle: 0,
+        }
+    }
+
+    /// Waits up to `timeout` for an event to be pending on any of the sockets, returning whether
+    /// one is.
+    ///
+    /// Waits indefinitely if `timeout` is `None`. Paused sockets are not waited on.
+    pub fn wait(&self, timeout: Option<Duration>) -> Result<bool> {
+        Ok(!wait_unpaused(&self.epoll, &self.sockets, &self.registered, timeout)?.is_empty())
+    }
+
+    /// Attempts to receive an event from any of the sockets without blocking, for use in a
+    /// `Future` implementation.
+    ///
+    /// See `Socket::poll_recv()` for the contract: the caller must wait for the file descriptor to
+    /// become readable and wake the task itself. The sockets take turns, as with `iter()`.
+    pub fn poll_recv(&self, cx: &mut Context<'_>) -> task::Poll<Result<Event>> {
+        let len = self.sockets.len();
+        let start = self.next.load(Ordering::Relaxed);
+
+        for i in 0..len {
+            let index = (start + i) % len;
+
+            if let task::Poll::Ready(result) = self.sockets[index].poll_recv(cx) {
+                self.next.store((index + 1) % len, Ordering::Relaxed);
+                return task::Poll::Ready(result);
+            }
+        }
+
+        task::Poll::Pending
+    }
+}
+
+/// Provides raw access to the epoll file descriptor the sockets are registered with.
+#[cfg(target_os = "linux")]
+impl AsRawFd for MultiSocket {
+    /// Returns the epoll file descriptor.
+    fn as_raw_fd(&self) -> RawFd {
+        self.epoll.as_raw_fd()
+    }
+}
+
+/// Provides raw access to the epoll file descriptor the sockets are registered with.
+#[cfg(target_os = "linux")]
+impl AsFd for MultiSocket {
+    /// Returns the epoll file descriptor.
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        unsafe { BorrowedFd::borrow_raw(self.as_raw_fd()) }
+    }
+}
+
+/// Iterator of events from several sockets
+#[cfg(target_os = "linux")]
+pub struct MultiSocketIter<'a> {
+    multi: &'a MultiSocket,
+    next: usize,
+    idle: usize,
+}
+
+#[cfg(target_os = "linux")]
+impl<'a> Iterator for MultiSocketIter<'a> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        let sockets = &self.multi.sockets;
+
+        // Stop once every socket came up empty in a row.
+        while self.idle < sockets.len() {
+            let socket = &sockets[self.next];
+            self.next = (self.next + 1) % sockets.len();
+
+            match socket.receive() {
+                Ok(event) => {
+                    self.idle = 0;
+                    return Some(event);
+                }
+                Err(_) => self.idle += 1,
+            }
+        }
+
+        None
+    }
+}
+
+/// The netlink multicast group the kernel sends its events to.
+#[cfg(target_os = "linux")]
+const KERNEL_GROUP: u32 = 1;
+
+/// Asks for the credentials of the sender of every message received on the netlink socket `fd`.
+#[cfg(target_os = "linux")]
+fn pass_credentials(fd: RawFd) -> Result<()> {
+    let enable: libc::c_int = 1;
+    let ret = unsafe {
+        libc::setsockopt(
+            fd,
+            libc::SOL_SOCKET,
+            libc::SO_PASSCRED,
+            &enable as *const libc::c_int as *const libc::c_void,
+            std::mem::size_of::<libc::c_int>() as libc::socklen_t,
+        )
+    };
+
+    if ret < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok(())
+}
+
+/// Receives one message from the netlink socket `fd` into `buf`, returning the length of the
+/// whole message, which exceeds `buf.len()` if it was truncated, and whether its sender is
+/// trusted.
+#[cfg(target_os = "linux")]
+fn recv_message(fd: RawFd, buf: &mut [u8]) -> Result<(usize, bool)> {
+    let mut addr: libc::sockaddr_nl = unsafe { std::mem::zeroed() };
+    // Room for one `SCM_CREDENTIALS` control message, as the `CMSG_SPACE` macro computes.
+    let mut control = [0u64; 8];
+    let mut iov = libc::iovec {
+        iov_base: buf.as_mut_ptr() as *mut libc::c_void,
+        iov_len: buf.len(),
+    };
+
+    let mut msg: libc::msghdr = unsafe { std::mem::zeroed() };
+    msg.msg_name = &mut addr as *mut libc::sockaddr_nl as *mut libc::c_void;
+    msg.msg_namelen = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut libc::c_void;
+    msg.msg_controllen = std::mem::size_of_val(&control) as _;
+
+    let len = unsafe { libc::recvmsg(fd, &mut msg, libc::MSG_TRUNC) };
+
+    if len < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+
+    Ok((len as usize, is_trusted_sender(&msg, &addr)))
+}
+
+/// Returns whether a message received by `recvmsg()` was sent by a process libudev would accept
+/// events from.
+///
+/// Like libudev, this requires the sender to be root, and messages sent to the kernel's group to
+/// come from the kernel. Unicast messages are dropped, as no sender is trusted to send them.
+#[cfg(target_os = "linux")]
+fn is_trusted_sender(msg: &libc::msghdr, addr: &libc::sockaddr_nl) -> bool {
+    if addr.nl_groups == 0 || (addr.nl_groups == KERNEL_GROUP && addr.nl_pid != 0) {
+        return false;
+    }
+
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(msg) };
+
+    while !cmsg.is_null() {
+        let header = unsafe { &*cmsg };
+
+        if header.cmsg_level == libc::SOL_SOCKET && header.cmsg_type == libc::SCM_CREDENTIALS {
+            let cred = unsafe { ptr::read_unaligned(libc::CMSG_DATA(cmsg) as *const libc::ucred) };
+            return cred.uid == 0;
+        }
+
+        cmsg = unsafe { libc::CMSG_NXTHDR(msg, cmsg) };
+    }
+
+    false
+}
+
+/// How often waiting on sockets checks whether a paused socket was resumed.
+///
+/// Resuming a socket doesn't wake up a thread that is waiting on it, so waiting is cut into
+/// slices of this length while any socket is paused.
+const PAUSE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Registers the sockets that are not paused with `epoll`, and unregisters the paused ones,
+/// returning whether any socket is paused.
+///
+/// A paused socket stays readable while its events are left pending, so with level-triggered
+/// epoll, waiting would return right away for it again and again.
+#[cfg(target_os = "linux")]
+fn register_unpaused(epoll: &Epoll, sockets: &[Socket], registered: &[AtomicBool]) -> Result<bool> {
+    let mut any_paused = false;
+
+    for (index, socket) in sockets.iter().enumerate() {
+        let paused = socket.is_paused();
+        any_paused |= paused;
+
+        // Only the caller that flips the flag updates the registration.
+        if registered[index].swap(!paused, Ordering::AcqRel) == paused {
+            if paused {
+                epoll.remove(socket.as_raw_fd())?;
+            } else {
+                epoll.add(socket.as_raw_fd(), index as u64)?;
+            }
+        }
+    }
+
+    Ok(any_paused)
+}
+
+/// Waits up to `timeout` for any of the sockets that are not paused to become readable,
+/// returning the tokens of the ready sockets, or nothing if the timeout elapsed.
+///
+/// Waits indefinitely if `timeout` is `None`, but checks every `PAUSE_POLL_INTERVAL` whether a
+/// paused socket was resumed.
+#[cfg(target_os = "linux")]
+fn wait_unpaused(
+    epoll: &Epoll,
+    sockets: &[Socket],
+    registered: &[AtomicBool],
+    timeout: Option<Duration>,
+) -> Result<Vec<u64>> {
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+    loop {
+        let any_paused = register_unpaused(epoll, sockets, registered)?;
+
+        let remaining = deadline.map(|deadline| deadline.saturating_duration_since(Instant::now()));
+
+        if !any_paused {
+            return epoll.wait(remaining);
+        }
+
+        let slice = remaining.map_or(PAUSE_POLL_INTERVAL, |r| r.min(PAUSE_POLL_INTERVAL));
+        let tokens = epoll.wait(Some(slice))?;
+
+        if !tokens.is_empty() || remaining.is_some_and(|r| r <= PAUSE_POLL_INTERVAL) {
+            return Ok(tokens);
+        }
+    }
+}
+
+/// Identifies a socket added to an `EventLoop`.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SocketId(usize);
+
+/// Waits for events on many sockets at once, telling which socket each event came from.
+///
+/// Unlike `MultiSocket`, which is meant to be driven by an external event loop, an `EventLoop`
+/// blocks until an event arrives. It owns its sockets, registered with a single epoll instance,
+/// so waiting costs the same no matter how many sockets there are. Like `MultiSocket`, it's only
+/// available on Linux.
+///
+/// ## Example
+///
+/// ```no_run
+/// let mut event_loop = udev::EventLoop::new().unwrap();
+///
+/// let drm = event_loop
+///     .add(udev::MonitorBuilder::new().unwrap().match_subsystem("drm").unwrap().listen().unwrap())
+///     .unwrap();
+/// event_loop
+///     .add(udev::MonitorBuilder::new().unwrap().match_subsystem("input").unwrap().listen().unwrap())
+///     .unwrap();
+///
+/// for (id, event) in event_loop.iter() {
+///     if id == drm {
+///         println!("display change: {:?}", event.syspath());
+///     } else {
+///         println!("input device {}: {:?}", event.event_type(), event.syspath());
+///     }
+/// }
+/// ```
+#[cfg(target_os = "linux")]
+pub struct EventLoop {
+    sockets: Vec<Socket>,
+    epoll: Epoll,
+    // Whether each socket is registered with `epoll`, which paused sockets are not.
+    registered: Vec<AtomicBool>,
+    // Sockets that epoll reported as readable and that may still have events pending.
+    ready: VecDeque<usize>,
+}
+
+#[cfg(target_os = "linux")]
+impl EventLoop {
+    /// Creates an event loop without any sockets.
+    pub fn new() -> Result<Self> {
+        Ok(Self {
+            sockets: Vec::new(),
+            epoll: Epoll::new()?,
+            registered: Vec::new(),
+            ready: VecDeque::new(),
+        })
+    }
+
+    /// Adds a socket to the event loop, returning the identifier its events are reported with.
+    pub fn add(&mut self, socket: Socket) -> Result<SocketId> {
+        let index = self.sockets.len();
+
+        self.epoll.add(socket.as_raw_fd(), index as u64)?;
+        self.sockets.push(socket);
+        self.registered.push(AtomicBool::new(true));
+
+        Ok(SocketId(index))
+    }
+
+    /// Returns the socket with the given identifier.
+    pub fn socket(&self, id: SocketId) -> Option<&Socket> {
+        self.sockets.get(id.0)
+    }
+
+    /// Waits up to `timeout` for an event on any of the sockets, returning the event and the
+    /// socket it came from, or `None` if the timeout elapsed.
+    ///
+    /// Waits indefinitely if `timeout` is `None`. Sockets with events pending take turns, so a
+    /// busy socket can't starve the others, and paused sockets are skipped. Receive buffer
+    /// overflows are counted in the socket's `stats()` and otherwise skipped.
+    pub fn next_event(&mut self, timeout: Option<Duration>) -> Result<Option<(SocketId, Event)>> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            while let Some(index) = self.ready.pop_front() {
+                match self.sockets[index].receive() {
+                    Ok(event) => {
+                        // Let the other ready sockets go first, then check this one again.
+                        self.ready.push_back(index);
+                        return Ok(Some((SocketId(index), event)));
+                    }
+                    Err(ref e) if e.raw_os_error() == Some(libc::EAGAIN) => {}
+                    // The message was dropped by a filter, the read was interrupted, or events
+                    // were lost; the socket may still have events pending.
+                    Err(ref e)
+                        if e.raw_os_error() == Some(0)
+                            || e.raw_os_error() == Some(libc::ENOBUFS)
+                            || e.kind() == std::io::ErrorKind::Interrupted =>
+                    {
+                        self.ready.push_front(index)
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            let timeout = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(Instant::now()) {
+                    Some(remaining) => Some(remaining),
+                    None => return Ok(None),
+                },
+                None => None,
+            };
+
+            let tokens = match wait_unpaused(&self.epoll, &self.sockets, &self.registered, timeout)
+            {
+                Ok(tokens) => tokens,
+                Err(ref e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if tokens.is_empty() && timeout.is_some() {
+                return Ok(None);
+            }
+
+            self.ready
+                .extend(tokens.into_iter().map(|token| token as usize));
+        }
+    }
+
+    /// Creates a blocking iterator of the events on all sockets.
+    ///
+    /// The iterator waits for each event indefinitely, and ends only if waiting or receiving
+    /// fails. Use `next_event()` to wait with a timeout or to find out about errors.
+    pub fn iter(&mut self) -> EventLoopIter<'_> {
+        EventLoopIter { event_loop: self }
+    }
+}
+
+/// Provides raw access to the epoll file descriptor the sockets are registered with.
+#[cfg(target_os = "linux")]
+impl AsRawFd for EventLoop {
+    /// Returns the epoll file descriptor.
+    fn as_raw_fd(&self) -> RawFd {
+        self.epoll.as_raw_fd()
+    }
+}
+
+/// Blocking iterator of the events on the sockets of an `EventLoop`
+#[cfg(target_os = "linux")]
+pub struct EventLoopIter<'a> {
+    event_loop: &'a mut EventLoop,
+}
+
+#[cfg(target_os = "linux")]
+impl<'a> Iterator for EventLoopIter<'a> {
+    type Item = (SocketId, Event);
+
+    fn next(&mut self) -> Option<(SocketId, Event)> {
+        self.event_loop.next_event(None).ok().flatten()
     }
 }
 
@@ -268,16 +1284,44 @@ impl Default for EventType {
     }
 }
 
-impl fmt::Display for EventType {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(match *self {
+impl EventType {
+    /// The event types that correspond to a known action.
+    const KNOWN: [EventType; 5] = [
+        EventType::Add,
+        EventType::Change,
+        EventType::Remove,
+        EventType::Bind,
+        EventType::Unbind,
+    ];
+
+    /// Returns the action string for the event type, as found in the `ACTION` property, e.g.
+    /// `"add"`.
+    ///
+    /// `Unknown` returns `"unknown"`, which is not an action udev sends.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
             EventType::Add => "add",
             EventType::Change => "change",
             EventType::Remove => "remove",
             EventType::Bind => "bind",
             EventType::Unbind => "unbind",
             EventType::Unknown => "unknown",
-        })
+        }
+    }
+
+    /// Returns the event type for the given action string, or `Unknown` if it's not recognized.
+    pub(crate) fn from_action(action: &OsStr) -> EventType {
+        EventType::KNOWN
+            .iter()
+            .copied()
+            .find(|event_type| action == event_type.as_str())
+            .unwrap_or(EventType::Unknown)
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
     }
 }
 
@@ -306,20 +1350,21 @@ impl Deref for Event {
 }
 
 impl Event {
+    /// Wraps a device as if it had been received from a monitor, for testing code that handles
+    /// events.
+    #[cfg(test)]
+    pub(crate) fn from_device(device: Device) -> Self {
+        Event { device }
+    }
+
     /// Returns the `EventType` corresponding to this event.
+    ///
+    /// Events always carry an action, so unlike `Device::action_type()`, this doesn't return an
+    /// `Option`. `Unknown` means the action isn't one this crate knows about.
     pub fn event_type(&self) -> EventType {
-        let value = match self.device.property_value("ACTION") {
-            Some(s) => s.to_str(),
-            None => None,
-        };
-
-        match value {
-            Some("add") => EventType::Add,
-            Some("change") => EventType::Change,
-            Some("remove") => EventType::Remove,
-            Some("bind") => EventType::Bind,
-            Some("unbind") => EventType::Unbind,
-            _ => EventType::Unknown,
+        match self.device.property_value("ACTION") {
+            Some(action) => EventType::from_action(action),
+            None => EventType::Unknown,
         }
     }
 
@@ -385,3 +1430,209 @@ impl Source for Socket {
         SourceFd(&self.as_raw_fd()).deregister(registry)
     }
 }
+
+#[cfg(all(feature = "mio06", target_os = "linux"))]
+impl Evented for MultiSocket {
+    fn register(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> std::io::Result<()> {
+        EventedFd(&self.as_raw_fd()).register(poll, token, interest, opts)
+    }
+
+    fn reregister(
+        &self,
+        poll: &Poll,
+        token: Token,
+        interest: Ready,
+        opts: PollOpt,
+    ) -> std::io::Result<()> {
+        EventedFd(&self.as_raw_fd()).reregister(poll, token, interest, opts)
+    }
+
+    fn deregister(&self, poll: &Poll) -> std::io::Result<()> {
+        EventedFd(&self.as_raw_fd()).deregister(poll)
+    }
+}
+
+#[cfg(all(
+    any(feature = "mio07", feature = "mio08", feature = "mio10"),
+    target_os = "linux"
+))]
+impl Source for MultiSocket {
+    fn register(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interest: Interest,
+    ) -> std::io::Result<()> {
+        SourceFd(&self.as_raw_fd()).register(registry, token, interest)
+    }
+
+    fn reregister(
+        &mut self,
+        registry: &Registry,
+        token: Token,
+        interest: Interest,
+    ) -> std::io::Result<()> {
+        SourceFd(&self.as_raw_fd()).reregister(registry, token, interest)
+    }
+
+    fn deregister(&mut self, registry: &Registry) -> std::io::Result<()> {
+        SourceFd(&self.as_raw_fd()).deregister(registry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_type_from_action() {
+        for &event_type in EventType::KNOWN.iter() {
+            assert_eq!(
+                EventType::from_action(OsStr::new(event_type.as_str())),
+                event_type
+            );
+        }
+
+        assert_eq!(EventType::from_action(OsStr::new("bind")), EventType::Bind);
+        assert_eq!(
+            EventType::from_action(OsStr::new("unbind")),
+            EventType::Unbind
+        );
+        assert_eq!(
+            EventType::from_action(OsStr::new("move")),
+            EventType::Unknown
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    const FORGED_MESSAGE: &[u8] = b"change@/devices/forged\0ACTION=change\0";
+
+    // Makes a kernel socket readable by sending it a message from another netlink socket. libudev
+    // drops the message, as it doesn't come from the kernel, but it's pending until received.
+    #[cfg(target_os = "linux")]
+    fn make_readable(socket: &Socket) {
+        unsafe {
+            let mut addr: libc::sockaddr_nl = std::mem::zeroed();
+            let mut len = std::mem::size_of::<libc::sockaddr_nl>() as libc::socklen_t;
+            let ret = libc::getsockname(
+                socket.as_raw_fd(),
+                &mut addr as *mut _ as *mut libc::sockaddr,
+                &mut len,
+            );
+            assert_eq!(ret, 0);
+
+            let sender = libc::socket(
+                libc::AF_NETLINK,
+                libc::SOCK_DGRAM | libc::SOCK_CLOEXEC,
+                libc::NETLINK_KOBJECT_UEVENT,
+            );
+            assert!(sender >= 0);
+
+            let sent = libc::sendto(
+                sender,
+                FORGED_MESSAGE.as_ptr() as *const libc::c_void,
+                FORGED_MESSAGE.len(),
+                0,
+                &addr as *const _ as *const libc::sockaddr,
+                len,
+            );
+            libc::close(sender);
+            assert_eq!(sent, FORGED_MESSAGE.len() as isize);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn read_raw_drops_untrusted_messages() {
+        let socket = Builder::new_kernel().unwrap().listen().unwrap();
+        make_readable(&socket);
+
+        // Events from the kernel may arrive meanwhile, but the forged message must not.
+        let mut buf = [0u8; 4096];
+        loop {
+            match socket.read_raw(&mut buf) {
+                Ok(len) => assert_ne!(&buf[..len.min(buf.len())], FORGED_MESSAGE),
+                Err(e) => {
+                    assert_eq!(e.kind(), std::io::ErrorKind::WouldBlock);
+                    break;
+                }
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn recv_message_reports_truncation() {
+        let socket = Builder::new_kernel().unwrap().listen().unwrap();
+        make_readable(&socket);
+
+        // Events from the kernel may arrive first, so skip to the forged message.
+        let mut buf = [0u8; 8];
+        loop {
+            let (len, trusted) = recv_message(socket.as_raw_fd(), &mut buf).unwrap();
+
+            if !trusted {
+                assert_eq!(len, FORGED_MESSAGE.len());
+                assert_eq!(&buf, &FORGED_MESSAGE[..buf.len()]);
+                break;
+            }
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    fn thread_cpu_time() -> Duration {
+        let mut ts: libc::timespec = unsafe { std::mem::zeroed() };
+        assert_eq!(
+            unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) },
+            0
+        );
+        Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32)
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn paused_sockets_are_not_waited_on() {
+        let socket = Builder::new_kernel().unwrap().listen().unwrap();
+        socket.pause();
+        make_readable(&socket);
+
+        let sockets = MultiSocket::new(vec![socket]).unwrap();
+        let start = thread_cpu_time();
+        assert!(!sockets.wait(Some(Duration::from_millis(300))).unwrap());
+        assert!(sockets.iter().next().is_none());
+        assert!(thread_cpu_time() - start < Duration::from_millis(100));
+
+        // Once resumed, the pending message wakes up the waiter again.
+        sockets.sockets()[0].resume();
+        assert!(sockets.wait(Some(Duration::from_millis(300))).unwrap());
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn event_loop_skips_paused_sockets() {
+        let socket = Builder::new_kernel().unwrap().listen().unwrap();
+        socket.pause();
+        make_readable(&socket);
+
+        let mut event_loop = EventLoop::new().unwrap();
+        event_loop.add(socket).unwrap();
+
+        let start = thread_cpu_time();
+        assert!(event_loop
+            .next_event(Some(Duration::from_millis(300)))
+            .unwrap()
+            .is_none());
+        assert!(thread_cpu_time() - start < Duration::from_millis(100));
+
+        let start = thread_cpu_time();
+        let socket = event_loop.socket(SocketId(0)).unwrap();
+        assert_eq!(socket.iter_coalesced(Duration::from_millis(300)).count(), 0);
+        assert!(thread_cpu_time() - start < Duration::from_millis(100));
+    }
+}
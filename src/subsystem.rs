@@ -0,0 +1,614 @@
+use std::convert::Infallible;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::ops::Deref;
+use std::os::unix::ffi::{OsStrExt, OsStringExt};
+use std::str::FromStr;
+
+use Device;
+
+/// The size of the sectors that the kernel reports block device sizes in, regardless of the
+/// device's actual logical block size.
+const SECTOR_SIZE: u64 = 512;
+
+/// Parses a sysfs attribute or udev property value.
+fn parse<T: FromStr>(value: Option<&OsStr>) -> Option<T> {
+    value?.to_str()?.trim().parse().ok()
+}
+
+/// Parses a sysfs attribute holding a `0` or `1` flag.
+fn parse_flag(value: Option<&OsStr>) -> Option<bool> {
+    parse::<u8>(value).map(|value| value != 0)
+}
+
+/// Parses a sysfs attribute given in millionths of a unit, such as microvolts, into the unit.
+fn parse_micro(value: Option<&OsStr>) -> Option<f64> {
+    parse::<i64>(value).map(|micro| micro as f64 / 1_000_000.0)
+}
+
+/// Parses a hardware address of the form `52:54:00:12:34:56`, as the kernel prints it, into a
+/// 6-byte MAC address.
+fn parse_mac_address(address: &OsStr) -> Option<[u8; 6]> {
+    let mut bytes = [0; 6];
+    let mut parts = address.to_str()?.trim().split(':');
+
+    for byte in bytes.iter_mut() {
+        let part = parts.next()?;
+
+        // `from_str_radix()` would also accept a sign.
+        if part.len() != 2 || !part.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+
+    if parts.next().is_some() {
+        return None;
+    }
+
+    Some(bytes)
+}
+
+/// Decodes a property value encoded by udev, which escapes unsafe bytes as `\xNN`.
+fn decode(value: &OsStr) -> OsString {
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i..].starts_with(b"\\x") {
+            let byte = bytes
+                .get(i + 2..i + 4)
+                .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+                .and_then(|hex| std::str::from_utf8(hex).ok())
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+            if let Some(byte) = byte {
+                decoded.push(byte);
+                i += 4;
+                continue;
+            }
+        }
+
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    OsString::from_vec(decoded)
+}
+
+/// A kernel subsystem, as returned by `Device::subsystem_typed()`.
+///
+/// The common subsystems have their own variants; any other subsystem is kept as `Other`, so
+/// matching on a `Subsystem` never loses information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Subsystem {
+    /// Disks and partitions, `block`.
+    Block,
+    /// Network interfaces, `net`.
+    Net,
+    /// USB devices and interfaces, `usb`.
+    Usb,
+    /// Input devices and their event nodes, `input`.
+    Input,
+    /// Terminals and serial ports, `tty`.
+    Tty,
+    /// Graphics cards and their connectors, `drm`.
+    Drm,
+    /// Raw HID devices, `hidraw`.
+    Hidraw,
+    /// Sound cards and their PCM and control nodes, `sound`.
+    Sound,
+    /// Batteries and AC adapters, `power_supply`.
+    Power,
+    /// Any other subsystem, holding its name.
+    Other(OsString),
+}
+
+impl Subsystem {
+    /// Returns the subsystem with the given kernel name.
+    pub(crate) fn from_name(name: &OsStr) -> Self {
+        match name.to_str() {
+            Some("block") => Subsystem::Block,
+            Some("net") => Subsystem::Net,
+            Some("usb") => Subsystem::Usb,
+            Some("input") => Subsystem::Input,
+            Some("tty") => Subsystem::Tty,
+            Some("drm") => Subsystem::Drm,
+            Some("hidraw") => Subsystem::Hidraw,
+            Some("sound") => Subsystem::Sound,
+            Some("power_supply") => Subsystem::Power,
+            _ => Subsystem::Other(name.to_owned()),
+        }
+    }
+
+    /// Returns the kernel name of the subsystem, e.g. `block` or `power_supply`.
+    pub fn name(&self) -> &OsStr {
+        OsStr::new(match *self {
+            Subsystem::Block => "block",
+            Subsystem::Net => "net",
+            Subsystem::Usb => "usb",
+            Subsystem::Input => "input",
+            Subsystem::Tty => "tty",
+            Subsystem::Drm => "drm",
+            Subsystem::Hidraw => "hidraw",
+            Subsystem::Sound => "sound",
+            Subsystem::Power => "power_supply",
+            Subsystem::Other(ref name) => return name,
+        })
+    }
+}
+
+/// Parses a kernel subsystem name. This never fails, as unknown names become `Other`.
+impl FromStr for Subsystem {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Infallible> {
+        Ok(Self::from_name(OsStr::new(s)))
+    }
+}
+
+/// Formats the kernel name of the subsystem.
+impl fmt::Display for Subsystem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name().to_string_lossy())
+    }
+}
+
+/// A typed view of a device in the `block` subsystem, i.e. a disk or a partition.
+///
+/// Created by `Device::as_block()`.
+#[derive(Clone, Debug)]
+pub struct BlockDevice {
+    pub(crate) device: Device,
+}
+
+impl BlockDevice {
+    /// Returns the size of the device in bytes.
+    pub fn size_bytes(&self) -> Option<u64> {
+        parse::<u64>(self.device.attribute_value("size")).map(|sectors| sectors * SECTOR_SIZE)
+    }
+
+    /// Returns whether the device is backed by rotational media, like a hard disk.
+    ///
+    /// For partitions, this is read from the disk that the partition belongs to.
+    pub fn is_rotational(&self) -> Option<bool> {
+        parse_flag(self.disk()?.attribute_value("queue/rotational"))
+    }
+
+    /// Returns whether the device uses removable media, like a card reader.
+    ///
+    /// For partitions, this is read from the disk that the partition belongs to.
+    pub fn removable(&self) -> Option<bool> {
+        parse_flag(self.disk()?.attribute_value("removable"))
+    }
+
+    /// Returns the partition number, or `None` if the device is not a partition.
+    pub fn partition_number(&self) -> Option<u32> {
+        parse(self.device.attribute_value("partition"))
+            .or_else(|| parse(self.device.property_value("PARTN")))
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the disk the device belongs to, which is the device itself unless it's a partition.
+    fn disk(&self) -> Option<Device> {
+        if self.device.devtype() == Some(OsStr::new("partition")) {
+            self.device.parent()
+        } else {
+            Some(self.device.clone())
+        }
+    }
+}
+
+/// Provides access to the generic device API.
+impl Deref for BlockDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// A typed view of a device in the `net` subsystem, i.e. a network interface.
+///
+/// Created by `Device::as_net()`.
+#[derive(Clone, Debug)]
+pub struct NetDevice {
+    pub(crate) device: Device,
+}
+
+impl NetDevice {
+    /// Returns the name of the interface, e.g. `eth0`.
+    pub fn interface(&self) -> &OsStr {
+        self.device
+            .property_value("INTERFACE")
+            .unwrap_or_else(|| self.device.sysname())
+    }
+
+    /// Returns the kernel's index of the interface.
+    pub fn ifindex(&self) -> Option<u32> {
+        parse(self.device.attribute_value("ifindex"))
+    }
+
+    /// Returns the hardware address of the interface, e.g. `52:54:00:12:34:56`.
+    pub fn address(&self) -> Option<&OsStr> {
+        self.device.attribute_value("address")
+    }
+
+    /// Returns the hardware address of the interface as an Ethernet MAC address.
+    ///
+    /// Returns `None` if the address is not a 6-byte address, as on InfiniBand interfaces, or
+    /// can't be parsed.
+    pub fn mac_address(&self) -> Option<[u8; 6]> {
+        parse_mac_address(self.address()?)
+    }
+
+    /// Returns the maximum transmission unit of the interface in bytes.
+    pub fn mtu(&self) -> Option<u32> {
+        parse(self.device.attribute_value("mtu"))
+    }
+
+    /// Returns the RFC 2863 operational state of the interface, e.g. `up` or `down`.
+    pub fn operstate(&self) -> Option<&OsStr> {
+        self.device.attribute_value("operstate")
+    }
+
+    /// Returns whether the interface has a physical link.
+    ///
+    /// Returns `None` if the interface is down, as the kernel doesn't report the carrier then.
+    pub fn carrier(&self) -> Option<bool> {
+        parse_flag(self.device.attribute_value("carrier"))
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// Provides access to the generic device API.
+impl Deref for NetDevice {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// A typed view of a partition of a block device.
+///
+/// Positions and sizes are given in 512-byte sectors, the unit the kernel uses for them in sysfs
+/// regardless of the disk's logical block size. Use `start_bytes()` and `size_bytes()` to avoid
+/// scaling them by the wrong sector size.
+///
+/// Created by `Device::as_partition()`.
+#[derive(Clone, Debug)]
+pub struct Partition {
+    pub(crate) device: Device,
+}
+
+impl Partition {
+    /// Returns the number of the partition in the partition table, e.g. `1` for `sda1`.
+    pub fn number(&self) -> Option<u32> {
+        parse(self.device.property_value("ID_PART_ENTRY_NUMBER"))
+            .or_else(|| parse(self.device.attribute_value("partition")))
+    }
+
+    /// Returns the unique identifier of the partition, from `ID_PART_ENTRY_UUID`.
+    ///
+    /// For GPT partitions this is the partition's UUID; for MBR partitions, it's derived from the
+    /// disk signature, e.g. `0c2a4f9e-01`.
+    pub fn uuid(&self) -> Option<&OsStr> {
+        self.device.property_value("ID_PART_ENTRY_UUID")
+    }
+
+    /// Returns the type of the partition, from `ID_PART_ENTRY_TYPE`.
+    ///
+    /// For GPT partitions this is the partition type UUID; for MBR partitions, it's the partition
+    /// type byte, e.g. `0x83`.
+    pub fn type_uuid(&self) -> Option<&OsStr> {
+        self.device.property_value("ID_PART_ENTRY_TYPE")
+    }
+
+    /// Returns the sector at which the partition starts on its disk.
+    pub fn start_sector(&self) -> Option<u64> {
+        parse(self.device.attribute_value("start"))
+            .or_else(|| parse(self.device.property_value("ID_PART_ENTRY_OFFSET")))
+    }
+
+    /// Returns the size of the partition in sectors.
+    pub fn size_sectors(&self) -> Option<u64> {
+        parse(self.device.attribute_value("size"))
+            .or_else(|| parse(self.device.property_value("ID_PART_ENTRY_SIZE")))
+    }
+
+    /// Returns the offset at which the partition starts on its disk, in bytes.
+    pub fn start_bytes(&self) -> Option<u64> {
+        self.start_sector().map(|sectors| sectors * SECTOR_SIZE)
+    }
+
+    /// Returns the size of the partition in bytes.
+    pub fn size_bytes(&self) -> Option<u64> {
+        self.size_sectors().map(|sectors| sectors * SECTOR_SIZE)
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// Provides access to the generic device API.
+impl Deref for Partition {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// A typed view of a device in the `power_supply` subsystem, i.e. a battery, a UPS or an AC
+/// adapter.
+///
+/// The kernel reports energy, charge, voltage, current and power in micro-units; the accessors
+/// convert them to watt-hours, amp-hours, volts, amps and watts. Which of them are available
+/// depends on the device: batteries usually report either energy or charge, but not both.
+///
+/// Created by `Device::as_power_supply()`.
+#[derive(Clone, Debug)]
+pub struct PowerSupply {
+    pub(crate) device: Device,
+}
+
+impl PowerSupply {
+    /// Returns the type of the power supply, e.g. `Battery`, `UPS` or `Mains`.
+    pub fn supply_type(&self) -> Option<&OsStr> {
+        self.device.attribute_value("type")
+    }
+
+    /// Returns whether the power supply is a battery.
+    pub fn is_battery(&self) -> bool {
+        self.supply_type() == Some(OsStr::new("Battery"))
+    }
+
+    /// Returns the charging status, e.g. `Charging`, `Discharging`, `Full` or `Not charging`.
+    pub fn status(&self) -> Option<&OsStr> {
+        self.device.attribute_value("status")
+    }
+
+    /// Returns the remaining capacity in percent.
+    pub fn capacity_percent(&self) -> Option<u8> {
+        parse(self.device.attribute_value("capacity"))
+    }
+
+    /// Returns whether an AC adapter or UPS is supplying power.
+    pub fn online(&self) -> Option<bool> {
+        parse_flag(self.device.attribute_value("online"))
+    }
+
+    /// Returns the remaining energy in watt-hours.
+    pub fn energy_now(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("energy_now"))
+    }
+
+    /// Returns the energy when fully charged in watt-hours.
+    pub fn energy_full(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("energy_full"))
+    }
+
+    /// Returns the remaining charge in amp-hours.
+    pub fn charge_now(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("charge_now"))
+    }
+
+    /// Returns the charge when fully charged in amp-hours.
+    pub fn charge_full(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("charge_full"))
+    }
+
+    /// Returns the current voltage in volts.
+    pub fn voltage_now(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("voltage_now"))
+    }
+
+    /// Returns the current in amps. Some drivers report a negative current while discharging.
+    pub fn current_now(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("current_now"))
+    }
+
+    /// Returns the power in watts. Some drivers report a negative power while discharging.
+    pub fn power_now(&self) -> Option<f64> {
+        parse_micro(self.device.attribute_value("power_now"))
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// Provides access to the generic device API.
+impl Deref for PowerSupply {
+    type Target = Device;
+
+    fn deref(&self) -> &Device {
+        &self.device
+    }
+}
+
+/// The filesystem properties that udev attaches to block devices, as probed by blkid.
+///
+/// Created by `Device::filesystem()`.
+#[derive(Clone, Debug)]
+pub struct FsInfo {
+    pub(crate) device: Device,
+}
+
+impl FsInfo {
+    /// Returns the type of the filesystem, e.g. `ext4` or `vfat`, from `ID_FS_TYPE`.
+    pub fn fstype(&self) -> &OsStr {
+        self.device
+            .property_value("ID_FS_TYPE")
+            .unwrap_or_else(|| OsStr::new(""))
+    }
+
+    /// Returns the UUID of the filesystem, from `ID_FS_UUID_ENC` or `ID_FS_UUID`.
+    pub fn uuid(&self) -> Option<OsString> {
+        self.decoded("ID_FS_UUID")
+    }
+
+    /// Returns the label of the filesystem, from `ID_FS_LABEL_ENC` or `ID_FS_LABEL`.
+    ///
+    /// In `ID_FS_LABEL`, udev replaces whitespace and other unsafe characters with `_`, so the
+    /// label is read from the encoded variant and decoded where possible.
+    pub fn label(&self) -> Option<OsString> {
+        self.decoded("ID_FS_LABEL")
+    }
+
+    /// Returns what the filesystem is used for, e.g. `filesystem`, `raid` or `crypto`, from
+    /// `ID_FS_USAGE`.
+    pub fn usage(&self) -> Option<&OsStr> {
+        self.device.property_value("ID_FS_USAGE")
+    }
+
+    /// Returns the underlying device.
+    pub fn device(&self) -> &Device {
+        &self.device
+    }
+
+    /// Returns the decoded value of the `_ENC` variant of a property, falling back to the
+    /// property itself.
+    fn decoded(&self, property: &str) -> Option<OsString> {
+        match self.device.property_value(format!("{}_ENC", property)) {
+            Some(value) => Some(decode(value)),
+            None => self.device.property_value(property).map(OsStr::to_owned),
+        }
+    }
+}
+
+/// The capabilities that udev's `input_id` builtin detects for input devices, from the
+/// `ID_INPUT_*` properties.
+///
+/// Each flag is `true` if its property is set to `1`. A device can have several capabilities,
+/// e.g. a keyboard with a built-in touchpad. Capabilities are set both on the input device and on
+/// its event nodes.
+///
+/// Created by `Device::input_capabilities()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct InputCaps {
+    /// Has keys or buttons, from `ID_INPUT_KEY`.
+    pub key: bool,
+    /// Is a full keyboard, from `ID_INPUT_KEYBOARD`.
+    pub keyboard: bool,
+    /// Is a mouse, from `ID_INPUT_MOUSE`.
+    pub mouse: bool,
+    /// Is a pointing stick, from `ID_INPUT_POINTINGSTICK`.
+    pub pointingstick: bool,
+    /// Is a trackball, from `ID_INPUT_TRACKBALL`.
+    pub trackball: bool,
+    /// Is a touchpad, from `ID_INPUT_TOUCHPAD`.
+    pub touchpad: bool,
+    /// Is a touchscreen, from `ID_INPUT_TOUCHSCREEN`.
+    pub touchscreen: bool,
+    /// Is a graphics tablet, from `ID_INPUT_TABLET`.
+    pub tablet: bool,
+    /// Is the button pad of a graphics tablet, from `ID_INPUT_TABLET_PAD`.
+    pub tablet_pad: bool,
+    /// Is a joystick or gamepad, from `ID_INPUT_JOYSTICK`.
+    pub joystick: bool,
+    /// Is an accelerometer, from `ID_INPUT_ACCELEROMETER`.
+    pub accelerometer: bool,
+    /// Has switches, such as a lid switch, from `ID_INPUT_SWITCH`.
+    pub switch: bool,
+}
+
+impl InputCaps {
+    /// Reads the capabilities of an input device, which udev marks with `ID_INPUT=1`.
+    pub(crate) fn from_device(device: &Device) -> Option<Self> {
+        let flag = |property: &str| device.property_value(property) == Some(OsStr::new("1"));
+
+        if !flag("ID_INPUT") {
+            return None;
+        }
+
+        Some(InputCaps {
+            key: flag("ID_INPUT_KEY"),
+            keyboard: flag("ID_INPUT_KEYBOARD"),
+            mouse: flag("ID_INPUT_MOUSE"),
+            pointingstick: flag("ID_INPUT_POINTINGSTICK"),
+            trackball: flag("ID_INPUT_TRACKBALL"),
+            touchpad: flag("ID_INPUT_TOUCHPAD"),
+            touchscreen: flag("ID_INPUT_TOUCHSCREEN"),
+            tablet: flag("ID_INPUT_TABLET"),
+            tablet_pad: flag("ID_INPUT_TABLET_PAD"),
+            joystick: flag("ID_INPUT_JOYSTICK"),
+            accelerometer: flag("ID_INPUT_ACCELEROMETER"),
+            switch: flag("ID_INPUT_SWITCH"),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn mac(address: &str) -> Option<[u8; 6]> {
+        parse_mac_address(OsStr::new(address))
+    }
+
+    #[test]
+    fn parse_valid_mac_address() {
+        assert_eq!(
+            mac("52:54:00:ab:CD:ef"),
+            Some([0x52, 0x54, 0x00, 0xab, 0xcd, 0xef])
+        );
+    }
+
+    #[test]
+    fn parse_mac_address_with_trailing_newline() {
+        assert_eq!(
+            mac("52:54:00:12:34:56\n"),
+            Some([0x52, 0x54, 0x00, 0x12, 0x34, 0x56])
+        );
+    }
+
+    #[test]
+    fn parse_mac_address_rejects_other_lengths() {
+        // InfiniBand interfaces have 20-byte hardware addresses.
+        assert_eq!(
+            mac("80:00:02:08:fe:80:00:00:00:00:00:00:00:02:c9:03:00:0a:bc:de"),
+            None
+        );
+        assert_eq!(mac("52:54:00:12:34"), None);
+        assert_eq!(mac(""), None);
+    }
+
+    #[test]
+    fn parse_mac_address_rejects_malformed_octets() {
+        assert_eq!(mac("52:54:0:12:34:56"), None);
+        assert_eq!(mac("52:54:000:12:34:56"), None);
+        assert_eq!(mac("52:54:0g:12:34:56"), None);
+        assert_eq!(mac("52:54:+0:12:34:56"), None);
+    }
+
+    #[test]
+    fn decode_escapes() {
+        assert_eq!(decode(OsStr::new("WDC\\x20WD10")), OsStr::new("WDC WD10"));
+        assert_eq!(decode(OsStr::new("a\\x2Fb\\x2fc")), OsStr::new("a/b/c"));
+        assert_eq!(decode(OsStr::new("\\xff")).as_bytes(), &[0xff]);
+        assert_eq!(decode(OsStr::new("plain")), OsStr::new("plain"));
+    }
+
+    #[test]
+    fn decode_keeps_invalid_escapes() {
+        assert_eq!(decode(OsStr::new("end\\x2")), OsStr::new("end\\x2"));
+        assert_eq!(decode(OsStr::new("end\\x")), OsStr::new("end\\x"));
+        assert_eq!(decode(OsStr::new("\\xzz1")), OsStr::new("\\xzz1"));
+        assert_eq!(decode(OsStr::new("\\x+f")), OsStr::new("\\x+f"));
+    }
+}
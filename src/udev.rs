@@ -1,8 +1,12 @@
+#[cfg(not(feature = "send"))]
+use std::cell::RefCell;
 use std::io::Result;
 
+use std::path::Path;
+
 use ffi;
 
-use FromRaw;
+use {Device, Enumerator, FromRaw, MonitorBuilder};
 
 /// Rust wrapper for the `udev` struct which represents an opaque libudev context
 ///
@@ -15,6 +19,11 @@ use FromRaw;
 /// `udev` is a ref-counted struct, with references added and removed with `udev_ref` and
 /// `udef_unref` respectively.  This Rust wrapper takes advantage of that ref counting to implement
 /// `Clone` and `Drop`, so callers need not worry about any C-specific resource management.
+///
+/// Cloning a `Udev` only increments the reference count of the underlying context, so the clone
+/// refers to the same context as the original. There is no need to wrap it in an `Arc` or `Rc` to
+/// share it between objects; clone it instead. libudev has no notion of weak references, so every
+/// clone keeps the context alive until it is dropped.
 pub struct Udev {
     udev: *mut ffi::udev,
 }
@@ -44,12 +53,66 @@ impl Udev {
         let ptr = try_alloc!(unsafe { ffi::udev_new() });
         Ok(unsafe { Self::from_raw(ptr) })
     }
+
+    /// Creates an enumerator that uses this context.
+    ///
+    /// Equivalent to `Enumerator::with_udev(udev.clone())`.
+    pub fn enumerator(&self) -> Result<Enumerator> {
+        Enumerator::with_udev(self.clone())
+    }
+
+    /// Creates a udev event monitor that uses this context.
+    pub fn monitor(&self) -> Result<MonitorBuilder> {
+        MonitorBuilder::with_udev(self.clone())
+    }
+
+    /// Creates a device for a given syspath, using this context.
+    ///
+    /// Equivalent to `Device::from_syspath_with_context(udev.clone(), syspath)`.
+    pub fn device_from_syspath<P: AsRef<Path>>(&self, syspath: P) -> Result<Device> {
+        Device::from_syspath_with_context(self.clone(), syspath)
+    }
+
+    /// Returns the context shared by the convenience constructors on this thread, such as
+    /// `Device::from_syspath()`, creating it on first use.
+    ///
+    /// The `*_with_context` constructors bypass this cache, so they can be used to keep objects on
+    /// separate contexts.
+    #[cfg(not(feature = "send"))]
+    pub(crate) fn cached() -> Result<Self> {
+        thread_local! {
+            static CACHED: RefCell<Option<Udev>> = const { RefCell::new(None) };
+        }
+
+        CACHED.with(|cached| {
+            let mut cached = cached.borrow_mut();
+
+            match *cached {
+                Some(ref udev) => Ok(udev.clone()),
+                None => {
+                    let udev = Udev::new()?;
+                    *cached = Some(udev.clone());
+                    Ok(udev)
+                }
+            }
+        })
+    }
+
+    /// Creates a new context for a convenience constructor.
+    ///
+    /// With the `send` feature, objects can be moved to another thread while this thread keeps
+    /// using a shared context, and libudev contexts must not be used from several threads at once.
+    /// So unlike without the feature, contexts are not cached.
+    #[cfg(feature = "send")]
+    pub(crate) fn cached() -> Result<Self> {
+        Self::new()
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use AsRaw;
+    use {AsRaw, Enumerator};
 
     #[test]
     fn clone_drop() {
@@ -67,6 +130,51 @@ mod tests {
         }
     }
 
+    #[test]
+    fn clone_outlives_original() {
+        // A clone shares the context with the original and keeps it alive on its own.
+        let udev = Udev::new().unwrap();
+        let ptr = udev.as_raw();
+        let clone = udev.clone();
+
+        drop(udev);
+
+        assert_eq!(ptr, clone.as_raw());
+        Enumerator::with_udev(clone).unwrap();
+    }
+
+    #[test]
+    #[cfg(not(feature = "send"))]
+    fn convenience_constructors_share_context() {
+        use AsRawWithContext;
+
+        let first = Enumerator::new().unwrap();
+        let second = Enumerator::new().unwrap();
+
+        assert_eq!(first.udev().as_raw(), second.udev().as_raw());
+
+        // Explicitly created contexts are never shared.
+        let isolated = Enumerator::with_udev(Udev::new().unwrap()).unwrap();
+
+        assert_ne!(first.udev().as_raw(), isolated.udev().as_raw());
+    }
+
+    #[test]
+    fn child_objects_share_context() {
+        use AsRawWithContext;
+
+        let udev = Udev::new().unwrap();
+        let enumerator = udev.enumerator().unwrap();
+
+        assert_eq!(enumerator.udev().as_raw(), udev.as_raw());
+
+        let device = udev.device_from_syspath("/sys/devices/virtual/mem/null");
+
+        if let Ok(device) = device {
+            assert_eq!(device.udev().as_raw(), udev.as_raw());
+        }
+    }
+
     #[test]
     fn round_trip_to_raw_pointers() {
         // Make sure this can be made into a raw pointer, then back to a Rust type, and still works
@@ -1,8 +1,14 @@
 use std::ffi::{CStr, CString, OsStr};
 use std::io::Result;
+#[cfg(target_has_atomic = "64")]
+use std::sync::atomic::{AtomicU64, Ordering};
+#[cfg(not(target_has_atomic = "64"))]
+use std::sync::Mutex;
 
 use libc::{c_char, c_int};
 
+use ffi;
+
 use std::os::unix::prelude::*;
 
 pub unsafe fn ptr_to_os_str<'a>(ptr: *const c_char) -> Option<&'a OsStr> {
@@ -30,3 +36,112 @@ pub fn errno_to_result(errno: c_int) -> Result<()> {
         e => Err(std::io::Error::from_raw_os_error(-e)),
     }
 }
+
+/// A `u64` shared between threads, for counters and settings of types that may be `Sync`.
+///
+/// Some 32-bit targets, such as powerpc and mips, lack 64-bit atomics, so a mutex stands in for
+/// them there. All accesses are relaxed, as the value guards no other memory.
+pub(crate) struct SharedU64 {
+    #[cfg(target_has_atomic = "64")]
+    value: AtomicU64,
+    #[cfg(not(target_has_atomic = "64"))]
+    value: Mutex<u64>,
+}
+
+#[cfg(target_has_atomic = "64")]
+impl SharedU64 {
+    pub(crate) fn new(value: u64) -> Self {
+        Self {
+            value: AtomicU64::new(value),
+        }
+    }
+
+    pub(crate) fn load(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn store(&self, value: u64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    pub(crate) fn add(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+}
+
+#[cfg(not(target_has_atomic = "64"))]
+impl SharedU64 {
+    pub(crate) fn new(value: u64) -> Self {
+        Self {
+            value: Mutex::new(value),
+        }
+    }
+
+    pub(crate) fn load(&self) -> u64 {
+        *self.lock()
+    }
+
+    pub(crate) fn store(&self, value: u64) {
+        *self.lock() = value;
+    }
+
+    pub(crate) fn add(&self, value: u64) {
+        let mut current = self.lock();
+        *current = current.wrapping_add(value);
+    }
+
+    // The value is always valid, so a panic while the lock was held doesn't matter.
+    fn lock(&self) -> std::sync::MutexGuard<'_, u64> {
+        self.value.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Sets the calling thread's `errno`.
+///
+/// libudev doesn't reset `errno` on success, so it is cleared before calls whose failure is read
+/// from it, lest a stale value be reported.
+pub(crate) fn set_errno(value: c_int) {
+    unsafe { *errno_location() = value };
+}
+
+#[cfg(any(target_os = "linux", target_os = "android", target_os = "emscripten"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__errno_location()
+}
+
+#[cfg(any(
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "macos",
+    target_os = "ios"
+))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__error()
+}
+
+#[cfg(any(target_os = "netbsd", target_os = "openbsd"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__errno()
+}
+
+/// Checks whether `udev` is the context that a libudev object was created with, given the
+/// object's own record of its context.
+///
+/// Objects created by libudev without a context record a null pointer, which matches any context.
+pub(crate) fn context_matches(udev: *mut ffi::udev, created_with: *mut ffi::udev) -> bool {
+    created_with.is_null() || created_with == udev
+}
+
+/// Checks whether an error returned by this crate is transient, i.e. whether retrying the
+/// operation may succeed.
+///
+/// `ENODEV` and `ENOENT` (the device disappeared while it was being looked up), `EAGAIN` (no
+/// event is pending on a monitor socket) and `ENOBUFS` (the monitor socket's receive buffer
+/// overflowed) are considered transient. All other errors, including those that don't carry an OS
+/// error code, are considered permanent.
+pub fn is_transient(err: &std::io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::ENODEV) | Some(libc::ENOENT) | Some(libc::EAGAIN) | Some(libc::ENOBUFS)
+    )
+}
@@ -0,0 +1,74 @@
+//! Checks that objects created from an explicit `Udev` context share it, rather than each
+//! allocating a context of their own.
+//!
+//! Every object that wraps a libudev handle keeps its context alive, and objects derived from one
+//! another, such as the devices found by an enumerator or the parent of a device, use the context
+//! of the object they came from. Only the convenience constructors without a context argument,
+//! like `Device::from_syspath()`, pick a context themselves.
+
+extern crate udev;
+
+use udev::{AsRaw, AsRawWithContext, Device, DeviceCache, DeviceFactory, Enumerator, Udev};
+
+const NULL: &str = "/sys/devices/virtual/mem/null";
+
+#[test]
+fn enumerated_devices_share_context() {
+    let udev = Udev::new().unwrap();
+
+    let mut enumerator = Enumerator::with_udev(udev.clone()).unwrap();
+    enumerator.match_subsystem("mem").unwrap();
+
+    assert_eq!(enumerator.udev().as_raw(), udev.as_raw());
+
+    let devices: Vec<Device> = enumerator.scan_devices().unwrap().collect();
+    assert!(!devices.is_empty());
+
+    for device in devices {
+        assert_eq!(device.udev().as_raw(), udev.as_raw());
+    }
+
+    for device in enumerator.devices_lazy().unwrap() {
+        assert_eq!(device.udev().as_raw(), udev.as_raw());
+    }
+}
+
+#[test]
+fn devices_share_context() {
+    let udev = Udev::new().unwrap();
+
+    let device = Device::from_syspath_with_context(udev.clone(), NULL).unwrap();
+    assert_eq!(device.udev().as_raw(), udev.as_raw());
+
+    let device = udev.device_from_syspath(NULL).unwrap();
+    assert_eq!(device.udev().as_raw(), udev.as_raw());
+
+    for ancestor in device.ancestors() {
+        assert_eq!(ancestor.udev().as_raw(), udev.as_raw());
+    }
+}
+
+#[test]
+fn device_helpers_share_context() {
+    let udev = Udev::new().unwrap();
+
+    let factory = DeviceFactory::with_udev(udev.clone());
+    assert_eq!(factory.device(NULL).unwrap().udev().as_raw(), udev.as_raw());
+
+    let mut cache = DeviceCache::with_udev(udev.clone());
+    assert_eq!(cache.get(NULL).unwrap().udev().as_raw(), udev.as_raw());
+}
+
+#[test]
+fn monitors_share_context() {
+    let udev = Udev::new().unwrap();
+
+    let builder = udev.monitor().unwrap().match_subsystem("mem").unwrap();
+    assert_eq!(builder.udev().as_raw(), udev.as_raw());
+
+    let enumerator = udev.enumerator().unwrap();
+    assert_eq!(enumerator.udev().as_raw(), udev.as_raw());
+
+    // Listening hands the monitor, and its context, over to the socket.
+    builder.listen().unwrap();
+}